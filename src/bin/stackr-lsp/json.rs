@@ -0,0 +1,373 @@
+//! Minimal JSON values for the language server protocol.
+use std::fmt::{Display, Write};
+
+/// A JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Members of an object, in order.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parse a JSON document.
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            idx: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.whitespace();
+        if parser.idx < parser.chars.len() {
+            return Err(format!("Unexpected character at {}", parser.idx));
+        }
+        Ok(value)
+    }
+
+    /// Create an object from its members.
+    pub fn object(members: Vec<(&str, Json)>) -> Json {
+        Json::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// Returns the member of an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(member, _)| member == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value at the path of object members.
+    pub fn at(&self, path: &[&str]) -> Option<&Json> {
+        path.iter().try_fold(self, |value, key| value.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Json::Number(number) if *number >= 0.0 && number.fract() == 0.0 => {
+                Some(*number as usize)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Json {
+    fn from(string: &str) -> Self {
+        Json::String(string.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(string: String) -> Self {
+        Json::String(string)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(number: usize) -> Self {
+        Json::Number(number as f64)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<Vec<Json>> for Json {
+    fn from(values: Vec<Json>) -> Self {
+        Json::Array(values)
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(number) => write!(f, "{}", number),
+            Json::String(string) => write_string(f, string),
+            Json::Array(values) => {
+                f.write_char('[')?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_char(']')
+            }
+            Json::Object(members) => {
+                f.write_char('{')?;
+                for (idx, (key, value)) in members.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+/// Write a string as a JSON string literal.
+fn write_string(f: &mut std::fmt::Formatter<'_>, string: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for c in string.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\t' => f.write_str("\\t")?,
+            '\r' => f.write_str("\\r")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Deepest nesting of arrays and objects that is parsed, so parsing can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Parses a JSON document.
+struct Parser {
+    chars: Vec<char>,
+    idx: usize,
+    /// Number of arrays and objects being parsed.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.idx).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.idx += 1;
+        Some(c)
+    }
+
+    fn whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.idx += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(format!("Expected '{}' at {}", expected, self.idx)),
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: Json) -> Result<Json, String> {
+        for expected in literal.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.whitespace();
+        match self.peek() {
+            Some('n') => self.literal("null", Json::Null),
+            Some('t') => self.literal("true", Json::Bool(true)),
+            Some('f') => self.literal("false", Json::Bool(false)),
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some('[') | Some('{') => self.nested(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(format!("Unexpected value at {}", self.idx)),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.idx;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            self.idx += 1;
+        }
+        let number: String = self.chars[start..self.idx].iter().collect();
+        number
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| format!("Invalid number at {}", start))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            match self.bump() {
+                None => return Err("Unclosed string".to_string()),
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let c = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(format!("Invalid escape at {}", self.idx)),
+                    };
+                    string.push(c);
+                }
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    /// Read the hex digits of a `\u` escape, combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+        self.expect('\\')?;
+        self.expect('u')?;
+        let low = self.hex()?;
+        let code = 0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+        Ok(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        let start = self.idx;
+        let digits: String = (0..4).filter_map(|_| self.bump()).collect();
+        u32::from_str_radix(&digits, 16).map_err(|_| format!("Invalid unicode escape at {}", start))
+    }
+
+    /// Parse an array or object, up to `MAX_DEPTH` deep.
+    fn nested(&mut self) -> Result<Json, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("Nested deeper than {} at {}", MAX_DEPTH, self.idx));
+        }
+        self.depth += 1;
+        let value = if self.peek() == Some('[') {
+            self.array()
+        } else {
+            self.object()
+        };
+        self.depth -= 1;
+        value
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut values = vec![];
+        self.whitespace();
+        if self.peek() == Some(']') {
+            self.idx += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            self.whitespace();
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(values)),
+                _ => return Err(format!("Expected ',' or ']' at {}", self.idx)),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut members = vec![];
+        self.whitespace();
+        if self.peek() == Some('}') {
+            self.idx += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.whitespace();
+            let key = self.string()?;
+            self.whitespace();
+            self.expect(':')?;
+            members.push((key, self.value()?));
+            self.whitespace();
+            match self.bump() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(members)),
+                _ => return Err(format!("Expected ',' or '}}' at {}", self.idx)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_values() {
+        let text = r#"{"a":[1,2.5,-3],"b":{"c":null,"d":true},"e":"x\"y\n"}"#;
+        let value = Json::parse(text).unwrap();
+        assert_eq!(value.at(&["b", "d"]), Some(&Json::Bool(true)));
+        assert_eq!(value.get("e").unwrap().as_str(), Some("x\"y\n"));
+        assert_eq!(value.to_string(), text);
+    }
+
+    #[test]
+    fn parses_whitespace_and_escapes() {
+        let value =
+            Json::parse(" { \"s\" : \"\\u00e9\\ud83d\\ude00\\/\" , \"n\" : [ ] } ").unwrap();
+        assert_eq!(value.get("s").unwrap().as_str(), Some("é😀/"));
+        assert_eq!(value.get("n").unwrap().as_array(), Some(&[][..]));
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        assert!(Json::parse("{\"a\":1,}").is_err());
+        assert!(Json::parse("[1 2]").is_err());
+        assert!(Json::parse("\"open").is_err());
+        assert!(Json::parse("1 2").is_err());
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(Json::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Json::parse(&nested(100_000)).unwrap_err(),
+            "Nested deeper than 128 at 128"
+        );
+    }
+}
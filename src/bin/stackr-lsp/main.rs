@@ -0,0 +1,28 @@
+//! Language server for stackr scripts.
+//!
+//! Speaks the language server protocol over stdin and stdout, providing:
+//! - diagnostics for parse errors, unmatched `:` and `;`, and undefined words
+//! - hover with the stack effect and documentation of words
+//! - go to definition of words defined in any open document or `.stackr` file of the workspace
+//! - completion of built-in words and words defined in open documents
+//! - document symbols
+//!
+//! Usage:
+//! ```text
+//! stackr-lsp
+//! ```
+mod json;
+mod server;
+
+fn main() {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let exit_code = match server::serve(stdin.lock(), stdout.lock()) {
+        Ok(exit_code) => exit_code,
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        }
+    };
+    std::process::exit(exit_code);
+}
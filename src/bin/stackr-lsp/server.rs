@@ -0,0 +1,1203 @@
+//! Language server protocol over JSON-RPC.
+use crate::json::Json;
+use stackr_rs::*;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// JSON-RPC error code for messages that are not valid JSON.
+const PARSE_ERROR: i32 = -32700;
+/// JSON-RPC error code for unknown methods.
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// Largest message body that is read, in bytes.
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024 * 1024;
+/// Longest header line that is read, in bytes.
+const MAX_HEADER_LENGTH: u64 = 1024;
+
+/// LSP severity of errors.
+const SEVERITY_ERROR: usize = 1;
+/// LSP text document sync kind that sends the full text on every change.
+const SYNC_FULL: usize = 1;
+
+/// A document opened by the client, analysed once per change.
+struct Document {
+    text: String,
+    path: PathBuf,
+    symbols: Vec<Symbol>,
+    words: Vec<SourceWord>,
+    /// Errors of checking the document on its own.
+    errors: Vec<Err>,
+}
+
+impl Document {
+    fn new(text: String, path: PathBuf) -> Self {
+        let symbols = Interpreter::<()>::symbols(&text, Some(path.clone())).unwrap_or_default();
+        let words = Interpreter::<()>::source_words(&text, Some(path.clone())).unwrap_or_default();
+        let errors = Interpreter::new(())
+            .check(&text, Some(path.clone()))
+            .err()
+            .unwrap_or_default();
+        Self {
+            text,
+            path,
+            symbols,
+            words,
+            errors,
+        }
+    }
+}
+
+/// A `.stackr` file of the workspace.
+struct WorkspaceFile {
+    path: PathBuf,
+    text: String,
+    symbols: Vec<Symbol>,
+}
+
+/// Handles the messages of a client.
+pub struct Server {
+    /// Open documents by URI.
+    documents: BTreeMap<String, Document>,
+    /// Interpreter with only the built-in words, for their documentation.
+    builtins: Interpreter<()>,
+    /// Workspace directories searched for definitions that are not in an open document.
+    roots: Vec<PathBuf>,
+    /// Files of the workspace, read on the first lookup and dropped when a file changes.
+    workspace: Option<Vec<WorkspaceFile>>,
+    shutdown: bool,
+    exit_code: Option<i32>,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self {
+            documents: BTreeMap::new(),
+            builtins: Interpreter::new(()),
+            roots: vec![],
+            workspace: None,
+            shutdown: false,
+            exit_code: None,
+        }
+    }
+
+    /// Handle a message from the client, returning the messages to send back.
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        // Responses to requests of the server are not used.
+        let Some(method) = message.get("method").and_then(Json::as_str) else {
+            return vec![];
+        };
+        let params = message.get("params").unwrap_or(&Json::Null);
+
+        let result = match method {
+            "initialize" => {
+                self.roots = workspace_roots(params);
+                Ok(initialize_result())
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            }
+            "exit" => {
+                self.exit_code = Some(if self.shutdown { 0 } else { 1 });
+                return vec![];
+            }
+            "textDocument/didOpen" | "textDocument/didChange" | "textDocument/didClose" => {
+                return self.sync_document(method, params);
+            }
+            "textDocument/didSave" | "workspace/didChangeWatchedFiles" => {
+                self.workspace = None;
+                return vec![];
+            }
+            "textDocument/hover" => Ok(self.hover(params)),
+            "textDocument/definition" => Ok(self.definition(params)),
+            "textDocument/completion" => Ok(self.completion()),
+            "textDocument/documentSymbol" => Ok(self.document_symbols(params)),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method {}", method))),
+        };
+
+        // Notifications have no id and get no response.
+        match message.get("id") {
+            Some(id) => vec![response(id.clone(), result)],
+            None => vec![],
+        }
+    }
+
+    /// Update the open documents and publish the diagnostics of all of them,
+    /// as definitions in one document resolve words in the others.
+    /// Only the changed document is checked again.
+    fn sync_document(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        let Some(uri) = params.at(&["textDocument", "uri"]).and_then(Json::as_str) else {
+            return vec![];
+        };
+        let text = match method {
+            "textDocument/didOpen" => params.at(&["textDocument", "text"]),
+            "textDocument/didChange" => {
+                self.workspace = None;
+                params
+                    .get("contentChanges")
+                    .and_then(Json::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+            }
+            _ => None,
+        };
+
+        let mut messages = vec![];
+        match text.and_then(Json::as_str) {
+            Some(text) => {
+                let document = Document::new(text.to_string(), uri_path(uri));
+                self.documents.insert(uri.to_string(), document);
+            }
+            None => {
+                self.documents.remove(uri);
+                messages.push(publish_diagnostics(uri, vec![]));
+            }
+        }
+        for uri in self.documents.keys() {
+            messages.push(publish_diagnostics(uri, self.diagnostics(uri)));
+        }
+        messages
+    }
+
+    /// Returns the parse errors and undefined words of a document.
+    fn diagnostics(&self, uri: &str) -> Vec<Json> {
+        let document = &self.documents[uri];
+        let defined_elsewhere: HashSet<&str> = self
+            .documents
+            .iter()
+            .filter(|(other, _)| *other != uri)
+            .flat_map(|(_, document)| &document.symbols)
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+
+        document
+            .errors
+            .iter()
+            .filter(|(message, _)| {
+                !message
+                    .strip_prefix("Undefined word: ")
+                    .is_some_and(|word| defined_elsewhere.contains(word))
+            })
+            .map(|(message, location)| {
+                let length = document
+                    .words
+                    .iter()
+                    .find(|word| word.location == *location)
+                    .map_or(1, |word| utf16_len(&word.name));
+                Json::object(vec![
+                    ("range", range(&document.text, location, length)),
+                    ("severity", SEVERITY_ERROR.into()),
+                    ("source", "stackr".into()),
+                    ("message", message.clone().into()),
+                ])
+            })
+            .collect()
+    }
+
+    /// Returns the stack effect and documentation of the word at the position.
+    fn hover(&self, params: &Json) -> Json {
+        let Some((uri, word)) = self.word_at(params) else {
+            return Json::Null;
+        };
+        let text = &self.documents[uri].text;
+        let contents = match self.find_symbol(uri, &word.name) {
+            Some((_, symbol)) => {
+                let documentation = symbol.documentation.as_ref();
+                let header = match documentation {
+                    Some(documentation) if !documentation.stack_modification.is_empty() => {
+                        format!(": {} ( {} )", symbol.name, documentation.stack_modification)
+                    }
+                    _ => format!("{} {}", keyword(symbol.kind), symbol.name),
+                };
+                markdown(&header, documentation)
+            }
+            None => {
+                let Some(documentation) = self.builtins.word_documentation(&word.name) else {
+                    return Json::Null;
+                };
+                let header = if documentation.stack_modification.is_empty() {
+                    word.name.clone()
+                } else {
+                    format!("{} ( {} )", word.name, documentation.stack_modification)
+                };
+                markdown(&header, Some(documentation))
+            }
+        };
+
+        Json::object(vec![
+            (
+                "contents",
+                Json::object(vec![
+                    ("kind", "markdown".into()),
+                    ("value", contents.into()),
+                ]),
+            ),
+            ("range", range(text, &word.location, utf16_len(&word.name))),
+        ])
+    }
+
+    /// Returns where the word at the position is defined,
+    /// in any open document or else in the `.stackr` files of the workspace.
+    fn definition(&mut self, params: &Json) -> Json {
+        let Some((uri, word)) = self.word_at(params) else {
+            return Json::Null;
+        };
+        let found = self
+            .find_symbol(uri, &word.name)
+            .map(|(uri, symbol)| (uri.to_string(), self.documents[uri].text.clone(), symbol));
+        let (uri, text, symbol) = match found {
+            Some(found) => found,
+            None => match self.find_workspace_symbol(&word.name) {
+                Some(found) => found,
+                None => return Json::Null,
+            },
+        };
+        Json::object(vec![
+            ("uri", uri.into()),
+            (
+                "range",
+                range(&text, &symbol.location, utf16_len(&symbol.name)),
+            ),
+        ])
+    }
+
+    /// Returns all built-in words and the words defined in the open documents.
+    fn completion(&self) -> Json {
+        let mut labels = HashSet::new();
+        let mut items = vec![];
+        for name in self.builtins.word_names() {
+            labels.insert(name.to_string());
+            items.push(completion_item(
+                name,
+                COMPLETION_FUNCTION,
+                self.builtins.word_documentation(name),
+            ));
+        }
+        for symbol in self
+            .documents
+            .values()
+            .flat_map(|document| &document.symbols)
+        {
+            let kind = match symbol.kind {
+                SymbolKind::Test => continue,
+                SymbolKind::Word | SymbolKind::Deferred => COMPLETION_FUNCTION,
+                SymbolKind::Variable | SymbolKind::Value => COMPLETION_VARIABLE,
+                SymbolKind::Constant => COMPLETION_CONSTANT,
+                SymbolKind::Struct => COMPLETION_STRUCT,
+            };
+            if labels.insert(symbol.name.clone()) {
+                items.push(completion_item(
+                    &symbol.name,
+                    kind,
+                    symbol.documentation.as_ref(),
+                ));
+            }
+        }
+        items.into()
+    }
+
+    /// Returns the symbols defined in a document.
+    fn document_symbols(&self, params: &Json) -> Json {
+        let Some(document) = params
+            .at(&["textDocument", "uri"])
+            .and_then(Json::as_str)
+            .and_then(|uri| self.documents.get(uri))
+        else {
+            return Json::Null;
+        };
+
+        let symbols = document
+            .symbols
+            .iter()
+            .map(|symbol| {
+                let kind = match symbol.kind {
+                    SymbolKind::Word | SymbolKind::Deferred => SYMBOL_FUNCTION,
+                    SymbolKind::Test => SYMBOL_METHOD,
+                    SymbolKind::Variable | SymbolKind::Value => SYMBOL_VARIABLE,
+                    SymbolKind::Constant => SYMBOL_CONSTANT,
+                    SymbolKind::Struct => SYMBOL_STRUCT,
+                };
+                let detail = match &symbol.documentation {
+                    Some(documentation) if !documentation.stack_modification.is_empty() => {
+                        format!("( {} )", documentation.stack_modification)
+                    }
+                    _ => keyword(symbol.kind).to_string(),
+                };
+                let range = range(&document.text, &symbol.location, utf16_len(&symbol.name));
+                Json::object(vec![
+                    ("name", symbol.name.clone().into()),
+                    ("detail", detail.into()),
+                    ("kind", kind.into()),
+                    ("range", range.clone()),
+                    ("selectionRange", range),
+                ])
+            })
+            .collect::<Vec<_>>();
+        symbols.into()
+    }
+
+    /// Returns the URI of the document and the word at the position of a request.
+    fn word_at<'a>(&'a self, params: &'a Json) -> Option<(&'a str, SourceWord)> {
+        let uri = params.at(&["textDocument", "uri"])?.as_str()?;
+        let line = params.at(&["position", "line"])?.as_usize()?;
+        let character = params.at(&["position", "character"])?.as_usize()?;
+
+        let document = self.documents.get(uri)?;
+        let word = document.words.iter().find(|word| {
+            let (word_line, start) = position(&document.text, &word.location);
+            word_line == line && (start..=start + utf16_len(&word.name)).contains(&character)
+        })?;
+        Some((uri, word.clone()))
+    }
+
+    /// Find the definition of a word, first in the document and then in the other open documents.
+    fn find_symbol<'a>(&'a self, uri: &'a str, name: &str) -> Option<(&'a str, Symbol)> {
+        let others = self
+            .documents
+            .keys()
+            .map(String::as_str)
+            .filter(|other| *other != uri);
+        std::iter::once(uri).chain(others).find_map(|uri| {
+            let symbol = self
+                .documents
+                .get(uri)?
+                .symbols
+                .iter()
+                .find(|symbol| symbol.name == name && symbol.kind != SymbolKind::Test)?;
+            Some((uri, symbol.clone()))
+        })
+    }
+
+    /// Find the definition of a word in the `.stackr` files of the workspace that are not open.
+    /// Returns the URI and text of the file with the symbol.
+    fn find_workspace_symbol(&mut self, name: &str) -> Option<(String, String, Symbol)> {
+        let roots = &self.roots;
+        let files = self.workspace.get_or_insert_with(|| read_workspace(roots));
+        let open: HashSet<&PathBuf> = self
+            .documents
+            .values()
+            .map(|document| &document.path)
+            .collect();
+        files
+            .iter()
+            .filter(|file| !open.contains(&file.path))
+            .find_map(|file| {
+                let symbol = file
+                    .symbols
+                    .iter()
+                    .find(|symbol| symbol.name == name && symbol.kind != SymbolKind::Test)?;
+                Some((path_uri(&file.path), file.text.clone(), symbol.clone()))
+            })
+    }
+}
+
+/// Read the `.stackr` files under the workspace roots and find their symbols.
+fn read_workspace(roots: &[PathBuf]) -> Vec<WorkspaceFile> {
+    roots
+        .iter()
+        .flat_map(|root| source_files(root))
+        .filter_map(|path| {
+            let text = std::fs::read_to_string(&path).ok()?;
+            let symbols = Interpreter::<()>::symbols(&text, Some(path.clone())).ok()?;
+            Some(WorkspaceFile {
+                path,
+                text,
+                symbols,
+            })
+        })
+        .collect()
+}
+
+/// LSP completion item kinds.
+const COMPLETION_FUNCTION: usize = 3;
+const COMPLETION_VARIABLE: usize = 6;
+const COMPLETION_CONSTANT: usize = 21;
+const COMPLETION_STRUCT: usize = 22;
+
+/// LSP symbol kinds.
+const SYMBOL_METHOD: usize = 6;
+const SYMBOL_FUNCTION: usize = 12;
+const SYMBOL_VARIABLE: usize = 13;
+const SYMBOL_CONSTANT: usize = 14;
+const SYMBOL_STRUCT: usize = 23;
+
+/// Returns the result of the `initialize` request.
+fn initialize_result() -> Json {
+    Json::object(vec![
+        (
+            "capabilities",
+            Json::object(vec![
+                (
+                    "textDocumentSync",
+                    Json::object(vec![
+                        ("openClose", true.into()),
+                        ("change", SYNC_FULL.into()),
+                        ("save", true.into()),
+                    ]),
+                ),
+                ("hoverProvider", true.into()),
+                ("definitionProvider", true.into()),
+                ("completionProvider", Json::object(vec![])),
+                ("documentSymbolProvider", true.into()),
+            ]),
+        ),
+        (
+            "serverInfo",
+            Json::object(vec![
+                ("name", "stackr-lsp".into()),
+                ("version", env!("CARGO_PKG_VERSION").into()),
+            ]),
+        ),
+    ])
+}
+
+/// Returns the word that defines a kind of symbol.
+fn keyword(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Word => ":",
+        SymbolKind::Test => "test:",
+        SymbolKind::Variable => "var",
+        SymbolKind::Constant => "constant",
+        SymbolKind::Value => "value",
+        SymbolKind::Deferred => "defer",
+        SymbolKind::Struct => "struct",
+    }
+}
+
+/// Returns the hover text of a word.
+fn markdown(header: &str, documentation: Option<&WordDocumentation>) -> String {
+    let mut markdown = format!("```stackr\n{}\n```", header);
+    if let Some(documentation) = documentation {
+        if !documentation.documentation.is_empty() {
+            markdown.push_str(&format!("\n\n{}", documentation.documentation));
+        }
+        if !documentation.example.is_empty() {
+            markdown.push_str(&format!("\n\nExample: `{}`", documentation.example));
+        }
+    }
+    markdown
+}
+
+fn completion_item(label: &str, kind: usize, documentation: Option<&WordDocumentation>) -> Json {
+    let mut item = vec![("label", label.into()), ("kind", kind.into())];
+    if let Some(documentation) = documentation {
+        if !documentation.stack_modification.is_empty() {
+            let detail = format!("( {} )", documentation.stack_modification);
+            item.push(("detail", detail.into()));
+        }
+        if !documentation.documentation.is_empty() {
+            item.push(("documentation", documentation.documentation.clone().into()));
+        }
+    }
+    Json::object(item)
+}
+
+/// Returns the zero-based line and character of a location in the text.
+/// LSP characters count UTF-16 code units, while location columns count characters from 1.
+fn position(text: &str, location: &Location) -> (usize, usize) {
+    let line = location.line.saturating_sub(1);
+    let character = text.lines().nth(line).map_or(0, |line| {
+        line.chars()
+            .take(location.column.saturating_sub(1))
+            .map(char::len_utf16)
+            .sum()
+    });
+    (line, character)
+}
+
+/// Returns the length of text in UTF-16 code units.
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Returns the LSP range of `length` UTF-16 code units starting at a location in the text.
+fn range(text: &str, location: &Location, length: usize) -> Json {
+    let (line, character) = position(text, location);
+    let position = |character: usize| {
+        Json::object(vec![("line", line.into()), ("character", character.into())])
+    };
+    Json::object(vec![
+        ("start", position(character)),
+        ("end", position(character + length)),
+    ])
+}
+
+/// Returns the workspace directories of the `initialize` request.
+fn workspace_roots(params: &Json) -> Vec<PathBuf> {
+    let folders: Vec<PathBuf> = params
+        .get("workspaceFolders")
+        .and_then(Json::as_array)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|folder| folder.get("uri").and_then(Json::as_str))
+        .map(uri_path)
+        .collect();
+    if !folders.is_empty() {
+        return folders;
+    }
+    match params.get("rootUri").and_then(Json::as_str) {
+        Some(uri) => vec![uri_path(uri)],
+        None => params
+            .get("rootPath")
+            .and_then(Json::as_str)
+            .map(PathBuf::from)
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Returns the `.stackr` files in a directory and its subdirectories, sorted by path.
+/// Hidden directories, `target` directories and symbolic links are skipped.
+fn source_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    let mut directories = vec![root.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let Ok(entries) = std::fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() && !name.starts_with('.') && name != "target" => {
+                    directories.push(path)
+                }
+                Ok(kind) if kind.is_file() && name.ends_with(".stackr") => files.push(path),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Returns the `file://` URI of a path.
+fn path_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+/// Returns the path of a `file://` URI.
+fn uri_path(uri: &str) -> PathBuf {
+    let path = uri.strip_prefix("file://").unwrap_or(uri).as_bytes();
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut idx = 0;
+    while idx < path.len() {
+        let escaped = path
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (path[idx], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                idx += 1;
+            }
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Json>) -> Json {
+    Json::object(vec![
+        ("jsonrpc", "2.0".into()),
+        ("method", "textDocument/publishDiagnostics".into()),
+        (
+            "params",
+            Json::object(vec![
+                ("uri", uri.into()),
+                ("diagnostics", diagnostics.into()),
+            ]),
+        ),
+    ])
+}
+
+fn response(id: Json, result: Result<Json, (i32, String)>) -> Json {
+    let result = match result {
+        Ok(result) => ("result", result),
+        Err((code, message)) => (
+            "error",
+            Json::object(vec![
+                ("code", Json::Number(code as f64)),
+                ("message", message.into()),
+            ]),
+        ),
+    };
+    Json::object(vec![("jsonrpc", "2.0".into()), ("id", id), result])
+}
+
+/// Read a message framed with a `Content-Length` header.
+/// Returns None at the end of the input, and an `InvalidData` error for malformed or oversized headers.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input
+            .by_ref()
+            .take(MAX_HEADER_LENGTH)
+            .read_line(&mut line)?
+            == 0
+        {
+            return Ok(None);
+        }
+        if !line.ends_with('\n') && line.len() as u64 == MAX_HEADER_LENGTH {
+            return Err(invalid(format!(
+                "Header longer than {} bytes",
+                MAX_HEADER_LENGTH
+            )));
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            let length = length.ok_or_else(|| invalid("Missing Content-Length header".into()))?;
+            let mut body = vec![0; length];
+            input.read_exact(&mut body)?;
+            return String::from_utf8(body)
+                .map(Some)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(invalid(format!("Invalid header: {}", line)));
+        };
+        if name.eq_ignore_ascii_case("Content-Length") {
+            let value = value.trim();
+            let value = value
+                .parse::<usize>()
+                .map_err(|_| invalid(format!("Invalid Content-Length: {}", value)))?;
+            if value > MAX_MESSAGE_LENGTH {
+                return Err(invalid(format!(
+                    "Content-Length {} is larger than {} bytes",
+                    value, MAX_MESSAGE_LENGTH
+                )));
+            }
+            length = Some(value);
+        }
+    }
+}
+
+/// Write a message framed with a `Content-Length` header.
+pub fn write_message(output: &mut impl Write, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// Serve a client until it sends `exit` or closes the input.
+/// Returns the exit code, which is 0 only if the client requested a shutdown first.
+pub fn serve(mut input: impl BufRead, mut output: impl Write) -> io::Result<i32> {
+    let mut server = Server::new();
+    while let Some(body) = read_message(&mut input)? {
+        let messages = match Json::parse(&body) {
+            Ok(message) => server.handle(&message),
+            Err(err) => vec![response(Json::Null, Err((PARSE_ERROR, err)))],
+        };
+        for message in messages.iter() {
+            write_message(&mut output, message)?;
+        }
+        if let Some(exit_code) = server.exit_code {
+            return Ok(exit_code);
+        }
+    }
+    Ok(if server.shutdown { 0 } else { 1 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const GAME: &str = "file:///project/game.stackr";
+    const LIB: &str = "file:///project/my%20lib.stackr";
+
+    fn notification(method: &str, params: Json) -> Json {
+        Json::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("method", method.into()),
+            ("params", params),
+        ])
+    }
+
+    fn request(id: usize, method: &str, params: Json) -> Json {
+        Json::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("id", id.into()),
+            ("method", method.into()),
+            ("params", params),
+        ])
+    }
+
+    fn open(uri: &str, text: &str) -> Json {
+        let document = Json::object(vec![
+            ("uri", uri.into()),
+            ("languageId", "stackr".into()),
+            ("version", 1.into()),
+            ("text", text.into()),
+        ]);
+        notification(
+            "textDocument/didOpen",
+            Json::object(vec![("textDocument", document)]),
+        )
+    }
+
+    fn at(id: usize, method: &str, uri: &str, line: usize, character: usize) -> Json {
+        let params = Json::object(vec![
+            ("textDocument", Json::object(vec![("uri", uri.into())])),
+            (
+                "position",
+                Json::object(vec![("line", line.into()), ("character", character.into())]),
+            ),
+        ]);
+        request(id, method, params)
+    }
+
+    /// Send the messages framed over a stream, returning the exit code and the messages sent back.
+    fn session(messages: &[Json]) -> (i32, Vec<Json>) {
+        let input: String = messages
+            .iter()
+            .map(|message| {
+                let body = message.to_string();
+                format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+            })
+            .collect();
+        let mut output = vec![];
+        let exit_code = serve(Cursor::new(input), &mut output).unwrap();
+
+        let mut output = Cursor::new(output);
+        let mut sent = vec![];
+        while let Some(body) = read_message(&mut output).unwrap() {
+            sent.push(Json::parse(&body).unwrap());
+        }
+        (exit_code, sent)
+    }
+
+    /// Returns the result of the response to the request with the id.
+    fn result(messages: &[Json], id: usize) -> Json {
+        messages
+            .iter()
+            .find(|message| message.get("id") == Some(&id.into()))
+            .and_then(|message| message.get("result"))
+            .cloned()
+            .unwrap()
+    }
+
+    /// Returns the last diagnostics published for the document.
+    fn diagnostics(messages: &[Json], uri: &str) -> Vec<Json> {
+        messages
+            .iter()
+            .rev()
+            .filter_map(|message| message.get("params"))
+            .find(|params| params.get("uri") == Some(&uri.into()))
+            .and_then(|params| params.get("diagnostics"))
+            .and_then(Json::as_array)
+            .unwrap()
+            .to_vec()
+    }
+
+    fn range_json(line: usize, start: usize, end: usize) -> String {
+        format!(
+            r#"{{"start":{{"line":{line},"character":{start}}},"end":{{"line":{line},"character":{end}}}}}"#
+        )
+    }
+
+    #[test]
+    fn initialize_and_shutdown() {
+        let (exit_code, messages) = session(&[
+            request(1, "initialize", Json::object(vec![])),
+            notification("initialized", Json::object(vec![])),
+            request(2, "shutdown", Json::Null),
+            notification("exit", Json::Null),
+        ]);
+        assert_eq!(exit_code, 0);
+        assert_eq!(messages.len(), 2);
+        let capabilities = result(&messages, 1);
+        assert_eq!(
+            capabilities.at(&["capabilities", "textDocumentSync", "change"]),
+            Some(&SYNC_FULL.into())
+        );
+        assert_eq!(
+            capabilities.at(&["capabilities", "hoverProvider"]),
+            Some(&true.into())
+        );
+        assert_eq!(result(&messages, 2), Json::Null);
+    }
+
+    #[test]
+    fn exit_without_shutdown_fails() {
+        let (exit_code, _) = session(&[notification("exit", Json::Null)]);
+        assert_eq!(exit_code, 1);
+    }
+
+    #[test]
+    fn unknown_requests_and_invalid_json_are_errors() {
+        let mut server = Server::new();
+        let messages = server.handle(&request(1, "workspace/unknown", Json::Null));
+        assert_eq!(
+            messages[0].at(&["error", "code"]),
+            Some(&Json::Number(METHOD_NOT_FOUND as f64))
+        );
+        assert!(server
+            .handle(&notification("$/unknown", Json::Null))
+            .is_empty());
+
+        let mut output = vec![];
+        serve(Cursor::new("Content-Length: 3\r\n\r\n{x}"), &mut output).unwrap();
+        let body = read_message(&mut Cursor::new(output)).unwrap().unwrap();
+        assert!(body.contains("\"code\":-32700"), "{}", body);
+    }
+
+    #[test]
+    fn invalid_headers_are_errors() {
+        let error = |input: &str| {
+            read_message(&mut Cursor::new(input))
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error("Content-Length: x\r\n\r\n"),
+            "Invalid Content-Length: x"
+        );
+        assert_eq!(
+            error("Content-Length: 999999999999\r\n\r\n"),
+            "Content-Length 999999999999 is larger than 67108864 bytes"
+        );
+        assert_eq!(
+            error("Content-Type: json\r\n\r\n{}"),
+            "Missing Content-Length header"
+        );
+        assert_eq!(error("oops\r\n"), "Invalid header: oops");
+        assert_eq!(error(&"x".repeat(2000)), "Header longer than 1024 bytes");
+        let body = read_message(&mut Cursor::new(
+            "content-length: 2\r\nContent-Type: json\r\n\r\n{}",
+        ));
+        assert_eq!(body.unwrap(), Some("{}".to_string()));
+    }
+
+    #[test]
+    fn parse_errors_are_published() {
+        let (_, messages) = session(&[
+            open(GAME, "1 dup\n\"oops"),
+            open(LIB, ": a \"\" \"\" \"\" 1 ;\n2 ;\n: b \"\" \"\" \"\" 2"),
+        ]);
+
+        let game = diagnostics(&messages, GAME);
+        assert_eq!(game.len(), 1);
+        assert_eq!(game[0].get("message"), Some(&"Unclosed string".into()));
+        assert_eq!(
+            game[0].get("range").unwrap().to_string(),
+            range_json(1, 0, 1)
+        );
+
+        let lib: Vec<String> = diagnostics(&messages, LIB)
+            .iter()
+            .map(|diagnostic| {
+                format!(
+                    "{} {}",
+                    diagnostic.get("message").unwrap().as_str().unwrap(),
+                    diagnostic.get("range").unwrap()
+                )
+            })
+            .collect();
+        assert_eq!(
+            lib,
+            [
+                format!("Found ; without a matching : {}", range_json(1, 2, 3)),
+                format!("No ; found, unable to compile {}", range_json(2, 0, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn undefined_words_resolve_across_documents() {
+        let change = notification(
+            "textDocument/didChange",
+            Json::object(vec![
+                ("textDocument", Json::object(vec![("uri", LIB.into())])),
+                (
+                    "contentChanges",
+                    vec![Json::object(vec![(
+                        "text",
+                        ": helper \"\" \"\" \"\" 1 ;".into(),
+                    )])]
+                    .into(),
+                ),
+            ]),
+        );
+        let close = notification(
+            "textDocument/didClose",
+            Json::object(vec![(
+                "textDocument",
+                Json::object(vec![("uri", LIB.into())]),
+            )]),
+        );
+        let (_, messages) =
+            session(&[open(GAME, "1 helper missing"), open(LIB, ""), change, close]);
+
+        let first = &messages[0].at(&["params", "diagnostics"]).unwrap();
+        assert_eq!(first.as_array().unwrap().len(), 2);
+        let undefined = |messages: &[Json]| -> Vec<String> {
+            diagnostics(messages, GAME)
+                .iter()
+                .map(|diagnostic| {
+                    format!(
+                        "{} {}",
+                        diagnostic.get("message").unwrap().as_str().unwrap(),
+                        diagnostic.get("range").unwrap()
+                    )
+                })
+                .collect()
+        };
+
+        // After the change, helper is defined in the other document.
+        let changed = messages.len() - 3;
+        assert_eq!(
+            undefined(&messages[..changed]),
+            [format!("Undefined word: missing {}", range_json(0, 9, 16))]
+        );
+        // Closing the document clears its diagnostics and leaves helper undefined again.
+        assert!(diagnostics(&messages, LIB).is_empty());
+        assert_eq!(undefined(&messages).len(), 2);
+    }
+
+    const WORDS: &str = ": squared \"squares a number\" \"n -- n\" \"2 squared\" dup * ;
+var score
+3 squared score !";
+
+    #[test]
+    fn hover_shows_documentation() {
+        let (_, messages) = session(&[
+            open(GAME, WORDS),
+            at(1, "textDocument/hover", GAME, 2, 2),
+            at(2, "textDocument/hover", GAME, 0, 51),
+            at(3, "textDocument/hover", GAME, 2, 12),
+            at(4, "textDocument/hover", GAME, 0, 30),
+        ]);
+
+        let hover = result(&messages, 1);
+        assert_eq!(
+            hover.at(&["contents", "value"]).unwrap().as_str(),
+            Some(
+                "```stackr\n: squared ( n -- n )\n```\n\nsquares a number\n\nExample: `2 squared`"
+            )
+        );
+        assert_eq!(hover.get("range").unwrap().to_string(), range_json(2, 2, 9));
+
+        let dup = result(&messages, 2);
+        let dup = dup.at(&["contents", "value"]).unwrap().as_str().unwrap();
+        assert!(
+            dup.starts_with("```stackr\ndup ( n -- n n )\n```\n\n"),
+            "{}",
+            dup
+        );
+
+        let score = result(&messages, 3);
+        assert_eq!(
+            score.at(&["contents", "value"]).unwrap().as_str(),
+            Some("```stackr\nvar score\n```")
+        );
+        // Strings are not words.
+        assert_eq!(result(&messages, 4), Json::Null);
+    }
+
+    #[test]
+    fn definitions_across_documents() {
+        let (_, messages) = session(&[
+            open(LIB, WORDS),
+            open(GAME, "\n  5 squared dup"),
+            at(1, "textDocument/definition", GAME, 1, 6),
+            at(2, "textDocument/definition", GAME, 1, 13),
+            at(3, "textDocument/definition", LIB, 2, 10),
+        ]);
+
+        let definition = result(&messages, 1);
+        assert_eq!(definition.get("uri"), Some(&LIB.into()));
+        assert_eq!(
+            definition.get("range").unwrap().to_string(),
+            range_json(0, 2, 9)
+        );
+        assert_eq!(result(&messages, 2), Json::Null);
+        assert_eq!(
+            result(&messages, 3).get("range").unwrap().to_string(),
+            range_json(1, 4, 9)
+        );
+    }
+
+    #[test]
+    fn positions_count_utf16_code_units() {
+        // The emoji is one character but two UTF-16 code units.
+        let (_, messages) = session(&[
+            open(GAME, "\"😀\" missing\n\"é😀\" dup"),
+            at(1, "textDocument/hover", GAME, 1, 7),
+        ]);
+        assert_eq!(
+            diagnostics(&messages, GAME)[0]
+                .get("range")
+                .unwrap()
+                .to_string(),
+            range_json(0, 5, 12)
+        );
+        assert_eq!(
+            result(&messages, 1).get("range").unwrap().to_string(),
+            range_json(1, 6, 9)
+        );
+    }
+
+    #[test]
+    fn completion_lists_known_words() {
+        let (_, messages) = session(&[
+            open(GAME, WORDS),
+            at(1, "textDocument/completion", GAME, 2, 0),
+        ]);
+        let items = result(&messages, 1);
+        let items = items.as_array().unwrap();
+        let item = |label: &str| {
+            items
+                .iter()
+                .find(|item| item.get("label") == Some(&label.into()))
+                .unwrap()
+        };
+        assert_eq!(item("dup").get("detail"), Some(&"( n -- n n )".into()));
+        assert_eq!(item("squared").get("detail"), Some(&"( n -- n )".into()));
+        assert_eq!(item("score").get("kind"), Some(&COMPLETION_VARIABLE.into()));
+    }
+
+    #[test]
+    fn document_symbols() {
+        let code = format!(
+            "{}\nstruct enemy {{ hp }}\ntest: squares 2 squared 4 assert-eq ;",
+            WORDS
+        );
+        let (_, messages) = session(&[
+            open(GAME, &code),
+            request(
+                1,
+                "textDocument/documentSymbol",
+                Json::object(vec![(
+                    "textDocument",
+                    Json::object(vec![("uri", GAME.into())]),
+                )]),
+            ),
+        ]);
+        let symbols: Vec<String> = result(&messages, 1)
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|symbol| {
+                format!(
+                    "{} {} {}",
+                    symbol.get("name").unwrap().as_str().unwrap(),
+                    symbol.get("detail").unwrap().as_str().unwrap(),
+                    symbol.get("kind").unwrap()
+                )
+            })
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                "squared ( n -- n ) 12",
+                "score var 13",
+                "enemy struct 23",
+                "squares test: 6"
+            ]
+        );
+    }
+
+    #[test]
+    fn definitions_in_workspace_files() {
+        let root =
+            std::env::temp_dir().join(format!("stackr_lsp_workspace_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::create_dir_all(root.join(".hidden")).unwrap();
+        std::fs::write(
+            root.join("lib/my words.stackr"),
+            "\n: helper \"\" \"\" \"\" 1 ;",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join(".hidden/other.stackr"),
+            ": hidden \"\" \"\" \"\" 1 ;",
+        )
+        .unwrap();
+
+        let game = path_uri(&root.join("game.stackr"));
+        let initialize = Json::object(vec![("rootUri", path_uri(&root).into())]);
+        let (_, messages) = session(&[
+            request(1, "initialize", initialize),
+            open(&game, "helper hidden"),
+            at(2, "textDocument/definition", &game, 0, 1),
+            at(3, "textDocument/definition", &game, 0, 8),
+        ]);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let definition = result(&messages, 2);
+        assert_eq!(
+            definition.get("uri").unwrap().as_str().unwrap(),
+            path_uri(&root.join("lib/my words.stackr"))
+        );
+        assert!(definition
+            .get("uri")
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .ends_with("/lib/my%20words.stackr"));
+        assert_eq!(
+            definition.get("range").unwrap().to_string(),
+            range_json(1, 2, 8)
+        );
+        assert_eq!(result(&messages, 3), Json::Null);
+    }
+
+    #[test]
+    fn workspace_files_are_read_again_after_a_save() {
+        let root = std::env::temp_dir().join(format!("stackr_lsp_saved_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let lib = root.join("lib.stackr");
+        std::fs::write(&lib, ": helper \"\" \"\" \"\" 1 ;").unwrap();
+
+        let game = path_uri(&root.join("game.stackr"));
+        let mut server = Server::new();
+        let initialize = Json::object(vec![("rootUri", path_uri(&root).into())]);
+        server.handle(&request(1, "initialize", initialize));
+        server.handle(&open(&game, "helper"));
+        let mut definition = |id| {
+            let messages = server.handle(&at(id, "textDocument/definition", &game, 0, 1));
+            result(&messages, id).get("range").unwrap().to_string()
+        };
+        assert_eq!(definition(2), range_json(0, 2, 8));
+
+        std::fs::write(&lib, "\n: helper \"\" \"\" \"\" 1 ;").unwrap();
+        assert_eq!(definition(3), range_json(0, 2, 8));
+        let saved = Json::object(vec![(
+            "textDocument",
+            Json::object(vec![("uri", path_uri(&lib).into())]),
+        )]);
+        server.handle(&notification("textDocument/didSave", saved));
+        let messages = server.handle(&at(4, "textDocument/definition", &game, 0, 1));
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            result(&messages, 4).get("range").unwrap().to_string(),
+            range_json(1, 2, 8)
+        );
+    }
+
+    #[test]
+    fn uris_are_decoded() {
+        assert_eq!(uri_path(LIB), PathBuf::from("/project/my lib.stackr"));
+        assert_eq!(uri_path("untitled:1"), PathBuf::from("untitled:1"));
+        assert_eq!(path_uri(&uri_path(LIB)), LIB);
+    }
+}
@@ -0,0 +1,359 @@
+//! Command-line runner for stackr scripts.
+//!
+//! Usage:
+//! ```text
+//! stackr run <file>            Run a script
+//! stackr trace [--json] <file> Run a script, tracing every instruction to stderr
+//! stackr profile [--folded] <file>
+//!                              Run a script, writing a profile of its words to stderr
+//! stackr coverage <file> <lcov>
+//!                              Run a script, writing the lines, words and branches run to an lcov file
+//! stackr test <file>...       Run the tests defined with `test:` in the scripts
+//! stackr repl                  Start a REPL
+//! stackr fmt [--write] <file>  Format a script, printing it or rewriting the file
+//! stackr check <file>          Check a script for errors without running it
+//! stackr doc                   Print the documentation of all built-in words
+//! ```
+use stackr_rs::*;
+use std::path::PathBuf;
+
+/// Exit code for errors in a script.
+const EXIT_ERROR: i32 = 1;
+/// Exit code for invalid command-line arguments.
+const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "Usage:
+    stackr run <file>            Run a script
+    stackr trace [--json] <file> Run a script, tracing every instruction to stderr
+    stackr profile [--folded] <file>
+                                 Run a script, writing a profile of its words to stderr
+    stackr coverage <file> <lcov>
+                                 Run a script, writing the lines, words and branches run to an lcov file
+    stackr test <file>...        Run the tests defined with `test:` in the scripts
+    stackr repl                  Start a REPL
+    stackr fmt [--write] <file>  Format a script, printing it or rewriting the file
+    stackr check <file>          Check a script for errors without running it
+    stackr doc                   Print the documentation of all built-in words";
+
+/// A subcommand of the binary.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run(PathBuf),
+    Trace { path: PathBuf, format: TraceFormat },
+    Profile { path: PathBuf, folded: bool },
+    Coverage { path: PathBuf, output: PathBuf },
+    Test(Vec<PathBuf>),
+    Repl,
+    Fmt { path: PathBuf, write: bool },
+    Check(PathBuf),
+    Doc,
+    Help,
+}
+
+/// Parse the command-line arguments, excluding the binary name.
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["run", file] => Ok(Command::Run(file.into())),
+        ["trace", file] => Ok(Command::Trace {
+            path: file.into(),
+            format: TraceFormat::Text,
+        }),
+        ["trace", "--json", file] | ["trace", file, "--json"] => Ok(Command::Trace {
+            path: file.into(),
+            format: TraceFormat::JsonLines,
+        }),
+        ["profile", file] => Ok(Command::Profile {
+            path: file.into(),
+            folded: false,
+        }),
+        ["profile", "--folded", file] | ["profile", file, "--folded"] => Ok(Command::Profile {
+            path: file.into(),
+            folded: true,
+        }),
+        ["coverage", file, output] => Ok(Command::Coverage {
+            path: file.into(),
+            output: output.into(),
+        }),
+        ["test", files @ ..] if !files.is_empty() => {
+            Ok(Command::Test(files.iter().map(PathBuf::from).collect()))
+        }
+        ["repl"] => Ok(Command::Repl),
+        ["fmt", file] => Ok(Command::Fmt {
+            path: file.into(),
+            write: false,
+        }),
+        ["fmt", "--write", file] | ["fmt", file, "--write"] => Ok(Command::Fmt {
+            path: file.into(),
+            write: true,
+        }),
+        ["check", file] => Ok(Command::Check(file.into())),
+        ["doc"] => Ok(Command::Doc),
+        ["help"] | ["--help"] | ["-h"] => Ok(Command::Help),
+        [] => Err("No command given".to_string()),
+        [command, ..] => Err(format!("Invalid arguments for '{}'", command)),
+    }
+}
+
+/// Print an error as `file:line:col: message`.
+fn print_error((message, location): &Err) {
+    eprintln!("{}: {}", location, message);
+}
+
+/// Read a script, printing an error if it can't be read.
+fn read_file(path: &PathBuf) -> Result<String, i32> {
+    std::fs::read_to_string(path).map_err(|e| {
+        eprintln!("{}: {}", path.display(), e);
+        EXIT_ERROR
+    })
+}
+
+/// Run a script. Returns the exit code of the process on failure.
+fn run_script(interpreter: &mut Interpreter<()>, path: PathBuf) -> Result<(), i32> {
+    let code = read_file(&path)?;
+    if let Err(err) = interpreter.evaluate(&code, Some(path)) {
+        print_error(&err);
+        return Err(EXIT_ERROR);
+    }
+    match interpreter.exit_code() {
+        Some(0) | None => Ok(()),
+        Some(code) => Err(code),
+    }
+}
+
+/// Run a command. Returns the exit code of the process on failure.
+fn run(command: Command) -> Result<(), i32> {
+    match command {
+        Command::Run(path) => run_script(&mut Interpreter::new(()), path),
+        Command::Trace { path, format } => {
+            let mut interpreter = Interpreter::new(());
+            interpreter.trace_to(Box::new(std::io::stderr()), format);
+            run_script(&mut interpreter, path)
+        }
+        Command::Profile { path, folded } => {
+            let mut interpreter = Interpreter::new(());
+            interpreter.start_profiling();
+            let result = run_script(&mut interpreter, path);
+            if let Some(profile) = interpreter.stop_profiling() {
+                match folded {
+                    true => eprint!("{}", profile.folded()),
+                    false => eprint!("{}", profile.report()),
+                }
+            }
+            result
+        }
+        Command::Coverage { path, output } => {
+            let mut interpreter = Interpreter::new(());
+            interpreter.start_coverage();
+            let result = run_script(&mut interpreter, path);
+            if let Some(coverage) = interpreter.stop_coverage() {
+                std::fs::write(&output, coverage.to_lcov()).map_err(|e| {
+                    eprintln!("{}: {}", output.display(), e);
+                    EXIT_ERROR
+                })?;
+            }
+            result
+        }
+        Command::Test(paths) => {
+            let (mut passed, mut failed) = (0, 0);
+            for path in paths {
+                let code = read_file(&path)?;
+                let mut interpreter = Interpreter::new(());
+                if let Err(err) = interpreter.evaluate(&code, Some(path)) {
+                    print_error(&err);
+                    failed += 1;
+                    continue;
+                }
+                for result in interpreter.run_tests() {
+                    println!("{}", result);
+                    match result.passed() {
+                        true => passed += 1,
+                        false => failed += 1,
+                    }
+                }
+            }
+            println!("{} passed, {} failed", passed, failed);
+            match failed {
+                0 => Ok(()),
+                _ => Err(EXIT_ERROR),
+            }
+        }
+        Command::Repl => {
+            let mut interpreter = Interpreter::new(());
+            interpreter.start_repl().map_err(|err| {
+                print_error(&err);
+                EXIT_ERROR
+            })?;
+            match interpreter.exit_code() {
+                Some(0) | None => Ok(()),
+                Some(code) => Err(code),
+            }
+        }
+        Command::Fmt { path, write } => {
+            let code = read_file(&path)?;
+            let formatted =
+                Interpreter::<()>::format_code(&code, Some(path.clone())).map_err(|err| {
+                    print_error(&err);
+                    EXIT_ERROR
+                })?;
+            if write {
+                std::fs::write(&path, formatted).map_err(|e| {
+                    eprintln!("{}: {}", path.display(), e);
+                    EXIT_ERROR
+                })?;
+            } else {
+                print!("{}", formatted);
+            }
+            Ok(())
+        }
+        Command::Check(path) => {
+            let code = read_file(&path)?;
+            let mut interpreter = Interpreter::new(());
+            interpreter.check(&code, Some(path)).map_err(|errors| {
+                for err in errors.iter() {
+                    print_error(err);
+                }
+                EXIT_ERROR
+            })
+        }
+        Command::Doc => {
+            Interpreter::new(()).print_documentation();
+            Ok(())
+        }
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(EXIT_USAGE);
+        }
+    };
+
+    if let Err(code) = run(command) {
+        std::process::exit(code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// A path in the temp directory that is unique to this process,
+    /// so concurrent test runs don't share files.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            parse_args(&args(&["run", "a.stackr"])),
+            Ok(Command::Run("a.stackr".into()))
+        );
+        assert_eq!(
+            parse_args(&args(&["trace", "--json", "a.stackr"])),
+            Ok(Command::Trace {
+                path: "a.stackr".into(),
+                format: TraceFormat::JsonLines
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["profile", "a.stackr", "--folded"])),
+            Ok(Command::Profile {
+                path: "a.stackr".into(),
+                folded: true
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["test", "a.stackr", "b.stackr"])),
+            Ok(Command::Test(vec!["a.stackr".into(), "b.stackr".into()]))
+        );
+        assert_eq!(parse_args(&args(&["repl"])), Ok(Command::Repl));
+        assert_eq!(
+            parse_args(&args(&["fmt", "--write", "a.stackr"])),
+            Ok(Command::Fmt {
+                path: "a.stackr".into(),
+                write: true
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["check", "a.stackr"])),
+            Ok(Command::Check("a.stackr".into()))
+        );
+        assert_eq!(parse_args(&args(&["doc"])), Ok(Command::Doc));
+    }
+
+    #[test]
+    fn invalid_arguments_return_err() {
+        assert_eq!(parse_args(&args(&[])), Err("No command given".to_string()));
+        assert_eq!(
+            parse_args(&args(&["run"])),
+            Err("Invalid arguments for 'run'".to_string())
+        );
+    }
+
+    #[test]
+    fn run_returns_exit_code() {
+        let path = temp_path("stackr_run_returns_exit_code.stackr");
+        std::fs::write(&path, "3 exit-with").unwrap();
+        assert_eq!(run(Command::Run(path.clone())), Err(3));
+
+        std::fs::write(&path, "1 2 +").unwrap();
+        assert_eq!(run(Command::Run(path.clone())), Ok(()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn coverage_writes_lcov() {
+        let path = temp_path("stackr_coverage_writes_lcov.stackr");
+        let output = temp_path("stackr_coverage_writes_lcov.info");
+        std::fs::write(&path, "1 if 2 end").unwrap();
+        let command = Command::Coverage {
+            path: path.clone(),
+            output: output.clone(),
+        };
+        assert_eq!(run(command), Ok(()));
+
+        let lcov = std::fs::read_to_string(&output).unwrap();
+        assert!(lcov.contains("DA:1,3\n"), "{}", lcov);
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(output).unwrap();
+    }
+
+    #[test]
+    fn test_returns_err_for_failed_tests() {
+        let path = temp_path("stackr_test_returns_err.stackr");
+        std::fs::write(&path, "test: passes 1 assert ;").unwrap();
+        assert_eq!(run(Command::Test(vec![path.clone()])), Ok(()));
+
+        std::fs::write(&path, "test: passes 1 assert ;\ntest: fails 0 assert ;").unwrap();
+        assert_eq!(run(Command::Test(vec![path.clone()])), Err(EXIT_ERROR));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn check_returns_err_for_undefined_words() {
+        let path = temp_path("stackr_check_returns_err.stackr");
+        std::fs::write(&path, "1 dupe").unwrap();
+        assert_eq!(run(Command::Check(path.clone())), Err(EXIT_ERROR));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn missing_file_returns_err() {
+        let path = temp_path("stackr_missing_file.stackr");
+        assert_eq!(run(Command::Run(path)), Err(EXIT_ERROR));
+    }
+}
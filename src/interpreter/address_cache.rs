@@ -6,6 +6,8 @@ pub(crate) struct AddressCache {
     pub read_mode_end: Address,
     /// Address of the compile mode end.
     pub compile_end: Address,
+    /// Address of the if statement.
+    pub if_statement: Address,
     /// Address of the else statement.
     pub else_statement: Address,
     /// Address of the end of a statement.
@@ -26,6 +28,7 @@ impl AddressCache {
         Self {
             read_mode_end: Address::default(),
             compile_end: Address::default(),
+            if_statement: Address::default(),
             else_statement: Address::default(),
             end_statement: Address::default(),
             break_statement: Address::default(),
@@ -44,6 +47,7 @@ impl AddressCache {
         cache.read_mode_end = interpreter.get_address("]");
         cache.compile_end = interpreter.get_address(";");
 
+        cache.if_statement = interpreter.get_address("if");
         cache.else_statement = interpreter.get_address("else");
         cache.end_statement = interpreter.get_address("end");
 
@@ -54,4 +58,18 @@ impl AddressCache {
 
         interpreter.address_cache = cache;
     }
+
+    /// Returns whether the address is a control flow word,
+    /// which runs or skips the instructions around it rather than being called.
+    pub fn is_control_flow(&self, address: Address) -> bool {
+        [
+            self.if_statement,
+            self.else_statement,
+            self.end_statement,
+            self.break_statement,
+            self.begin_statement,
+            self.loop_statement,
+        ]
+        .contains(&address)
+    }
 }
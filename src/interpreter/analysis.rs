@@ -0,0 +1,187 @@
+use super::*;
+
+/// What a symbol defined in source code is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// A word defined with `:`.
+    Word,
+    /// A test defined with `test:`.
+    Test,
+    /// A variable defined with `var`.
+    Variable,
+    /// A constant defined with `constant`.
+    Constant,
+    /// A value defined with `value`.
+    Value,
+    /// A deferred word defined with `defer`.
+    Deferred,
+    /// A record type declared with `struct`.
+    Struct,
+}
+
+/// A symbol defined in source code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    /// Name of the symbol.
+    pub name: String,
+    /// What the symbol is.
+    pub kind: SymbolKind,
+    /// Location of the name.
+    pub location: Location,
+    /// Documentation of a word defined with `:`.
+    pub documentation: Option<WordDocumentation>,
+}
+
+/// A word used in source code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceWord {
+    /// The word.
+    pub name: String,
+    /// Location of the first character of the word.
+    pub location: Location,
+}
+
+/// Returns the kind of symbol a word defines.
+fn symbol_kind(word: &str) -> Option<SymbolKind> {
+    match word {
+        ":" => Some(SymbolKind::Word),
+        "test:" => Some(SymbolKind::Test),
+        "var" => Some(SymbolKind::Variable),
+        "constant" => Some(SymbolKind::Constant),
+        "value" => Some(SymbolKind::Value),
+        "defer" => Some(SymbolKind::Deferred),
+        "struct" => Some(SymbolKind::Struct),
+        _ => None,
+    }
+}
+
+impl<State> Interpreter<State> {
+    /// Returns all words in source code with their locations, without loading it.
+    pub fn source_words(code: &str, path: Option<PathBuf>) -> Result<Vec<SourceWord>, Err> {
+        let words = tokenize(code, path)?
+            .into_iter()
+            .filter_map(|token| match token.kind {
+                TokenKind::Word(name) => Some(SourceWord {
+                    name,
+                    location: token.location,
+                }),
+                _ => None,
+            })
+            .collect();
+        Ok(words)
+    }
+
+    /// Returns the symbols defined in source code, in order, without loading it.
+    pub fn symbols(code: &str, path: Option<PathBuf>) -> Result<Vec<Symbol>, Err> {
+        let tokens = tokenize(code, path)?;
+        let string = |idx: usize| match tokens.get(idx).map(|token| &token.kind) {
+            Some(TokenKind::String(string)) => Some(string.clone()),
+            _ => None,
+        };
+
+        let mut symbols = vec![];
+        let mut idx = 0;
+        while idx < tokens.len() {
+            let TokenKind::Word(word) = &tokens[idx].kind else {
+                idx += 1;
+                continue;
+            };
+            if word == "[" {
+                // Words in read mode are pushed as addresses, not run.
+                while idx < tokens.len() && tokens[idx].kind != TokenKind::Word("]".into()) {
+                    idx += 1;
+                }
+                continue;
+            }
+            let (Some(kind), Some(TokenKind::Word(name))) = (
+                symbol_kind(word),
+                tokens.get(idx + 1).map(|token| &token.kind),
+            ) else {
+                idx += 1;
+                continue;
+            };
+
+            let documentation = match kind {
+                SymbolKind::Word => Some(WordDocumentation {
+                    documentation: string(idx + 2).unwrap_or_default().trim().to_string(),
+                    stack_modification: string(idx + 3)
+                        .unwrap_or_default()
+                        .replace("...", "..")
+                        .trim()
+                        .to_string(),
+                    example: string(idx + 4).unwrap_or_default(),
+                }),
+                _ => None,
+            };
+            symbols.push(Symbol {
+                name: name.clone(),
+                kind,
+                location: tokens[idx + 1].location.clone(),
+                documentation,
+            });
+            idx += 2;
+        }
+        Ok(symbols)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = ": squared \"squares a number\" \"n -- n\" \"2 squared\" dup * ;
+var score
+[ var skipped ]
+struct enemy { hp }
+test: squares 2 squared 4 assert-eq ;";
+
+    #[test]
+    fn finds_symbols() {
+        let symbols = Interpreter::<()>::symbols(CODE, None).unwrap();
+        let names: Vec<_> = symbols
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.name.as_str(),
+                    symbol.kind,
+                    symbol.location.to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("squared", SymbolKind::Word, "stdin:1:3".to_string()),
+                ("score", SymbolKind::Variable, "stdin:2:5".to_string()),
+                ("enemy", SymbolKind::Struct, "stdin:4:8".to_string()),
+                ("squares", SymbolKind::Test, "stdin:5:7".to_string()),
+            ]
+        );
+        let documentation = symbols[0].documentation.as_ref().unwrap();
+        assert_eq!(documentation.stack_modification, "n -- n");
+        assert_eq!(documentation.documentation, "squares a number");
+        assert_eq!(documentation.example, "2 squared");
+    }
+
+    #[test]
+    fn finds_words() {
+        let words = Interpreter::<()>::source_words("1 dup\n\"a b\" print", None).unwrap();
+        let words: Vec<_> = words
+            .iter()
+            .map(|word| (word.name.as_str(), word.location.to_string()))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                ("dup", "stdin:1:3".to_string()),
+                ("print", "stdin:2:7".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_strings_are_errors() {
+        let result = Interpreter::<()>::symbols(": a \"oops", None);
+        assert_eq!(result, Err(("Unclosed string".into(), (1, 5).into())));
+    }
+}
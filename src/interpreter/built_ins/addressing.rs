@@ -1,5 +1,73 @@
 use super::*;
 
+impl<State> Interpreter<State> {
+    /// Handle the word read in single word read mode.
+    pub(crate) fn read_single_word(
+        &mut self,
+        action: SingleWord,
+        address: Address,
+    ) -> Result<(), Err> {
+        match action {
+            SingleWord::Var => {
+                let variable_location = self.next_address;
+                self.next_address = self.next_address.next();
+                self.define_word(address, RamValue::Address(variable_location))?;
+            }
+            SingleWord::Constant => {
+                let value = self.pop()?;
+                self.define_word(address, RamValue::Constant(value))?;
+            }
+            SingleWord::Value => {
+                let value = self.pop()?;
+                self.define_word(address, RamValue::NamedValue(value))?;
+            }
+            SingleWord::To => {
+                let value = self.pop()?;
+                match self.ram.get_mut(&address) {
+                    Some(RamValue::NamedValue(current)) => *current = value,
+                    Some(RamValue::Constant(_)) => {
+                        return Err((
+                            format!("Unable to change constant: {}", self.get_name(address)),
+                            self.location(),
+                        ));
+                    }
+                    _ => {
+                        return Err((
+                            format!("Not a value: {}", self.get_name(address)),
+                            self.location(),
+                        ));
+                    }
+                }
+            }
+            SingleWord::Tick => {
+                if !self.ram.contains_key(&address) {
+                    return Err((
+                        format!("Unknown word: {}", self.get_name(address)),
+                        self.location(),
+                    ));
+                }
+                self.push_execution_token(address);
+            }
+            SingleWord::Defer => {
+                self.define_word(address, RamValue::Deferred(None))?;
+            }
+            SingleWord::Is => {
+                let target = self.pop_execution_token()?;
+                match self.ram.get_mut(&address) {
+                    Some(RamValue::Deferred(current)) => *current = Some(target),
+                    _ => {
+                        return Err((
+                            format!("Not a deferred word: {}", self.get_name(address)),
+                            self.location(),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
     interpreter.register_builtin(
         "var",
@@ -7,7 +75,62 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
         "Declares a variable. Calling the $name will return the address of the variable.",
         "var life \t 42 life set",
         |interpreter| {
-            interpreter.read_mode = ReadMode::SingleWord;
+            interpreter.read_mode = ReadMode::SingleWord(SingleWord::Var);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "constant",
+        "<value> constant $name --",
+        "Defines a constant. Calling the $name will put the value on the stack. Constants cannot be changed or redefined.",
+        "42 constant answer \t answer",
+        |interpreter| {
+            interpreter.read_mode = ReadMode::SingleWord(SingleWord::Constant);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "value",
+        "<value> value $name --",
+        "Defines a value. Calling the $name will put the value on the stack. Use 'to' to change it.",
+        "100 value hp \t hp",
+        |interpreter| {
+            interpreter.read_mode = ReadMode::SingleWord(SingleWord::Value);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "to",
+        "<value> to $name --",
+        "Changes a value defined with 'value'.",
+        "100 value hp \t 90 to hp",
+        |interpreter| {
+            interpreter.read_mode = ReadMode::SingleWord(SingleWord::To);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "defer",
+        "defer $name --",
+        "Declares a deferred word. Calling the $name calls the word it was set to with 'is'.",
+        "defer greet \t ' dup is greet",
+        |interpreter| {
+            interpreter.read_mode = ReadMode::SingleWord(SingleWord::Defer);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "is",
+        "xt is $name --",
+        "Sets the word a deferred word calls to the execution token on the stack.",
+        "defer greet \t ' dup is greet",
+        |interpreter| {
+            interpreter.read_mode = ReadMode::SingleWord(SingleWord::Is);
             Ok(())
         },
     );
@@ -21,6 +144,26 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
             let name = interpreter.pop_address()?;
             let value = interpreter.pop()?;
 
+            match interpreter.ram.get(&name) {
+                Some(RamValue::Constant(_)) => {
+                    return Err((
+                        format!("Unable to change constant: {}", interpreter.get_name(name)),
+                        interpreter.location(),
+                    ));
+                }
+                // Words are only replaced by redefining them, which applies seals and the redefinition policy.
+                Some(RamValue::BuiltIn(_))
+                | Some(RamValue::Compiled(_))
+                | Some(RamValue::Deferred(_))
+                | Some(RamValue::Record(_)) => {
+                    return Err((
+                        format!("Unable to set word: {}", interpreter.get_name(name)),
+                        interpreter.location(),
+                    ));
+                }
+                _ => {}
+            }
+
             interpreter.ram.insert(name, value.into());
             Ok(())
         },
@@ -29,20 +172,50 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
     interpreter.register_builtin(
         "@",
         "@address @ --",
-        "Evaluates an address on the stack.",
+        "Evaluates an address or execution token on the stack.",
         "var life 42 life set life @ -- 42",
         |interpreter| {
-            let address = interpreter.pop_address()?;
+            let address = match interpreter.pop()? {
+                StackValue::Address(address) | StackValue::ExecutionToken(address) => address,
+                _ => {
+                    return Err((
+                        "Expected an address or execution token".to_string(),
+                        interpreter.location(),
+                    ))
+                }
+            };
             interpreter.execute_instruction(Instruction::Address(address))?;
 
             Ok(())
         },
     );
 
+    interpreter.register_builtin(
+        "'",
+        "' $name -- xt",
+        "Puts an execution token for $name on the stack without running it. Use 'execute' to run it.",
+        "' dup \t 2 swap execute",
+        |interpreter| {
+            interpreter.read_mode = ReadMode::SingleWord(SingleWord::Tick);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "execute",
+        "xt execute --",
+        "Runs an execution token created with ' or get.",
+        "2 ' dup execute",
+        |interpreter| {
+            let address = interpreter.pop_execution_token()?;
+            interpreter.execute_instruction(Instruction::Address(address))
+        },
+    );
+
     interpreter.register_builtin(
         "get",
         "@name get -- <value>",
-        "Gets the value of a variable and puts it on the stack.",
+        "Gets the value of a variable and puts it on the stack. Getting a word puts an execution token for it on the stack, which can be run with '@'.",
         "life get",
         |interpreter| {
             let name = interpreter.pop_address()?;
@@ -53,14 +226,17 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
                 Some(RamValue::Address(address)) => {
                     interpreter.stack.push(address.into());
                 }
-                Some(RamValue::Compiled(_)) => {
-                    todo!("Implement get for compiled");
+                Some(RamValue::Constant(value)) | Some(RamValue::NamedValue(value)) => {
+                    interpreter.stack.push(value.clone());
                 }
-                Some(RamValue::BuiltIn(_)) => {
-                    // TODO: might be able to handle gracefully,
-                    // idk how right now though.
-                    let name = interpreter.get_name(name);
-                    panic!("Can't get value of built in function '{}'", name);
+                Some(RamValue::Compiled(_))
+                | Some(RamValue::BuiltIn(_))
+                | Some(RamValue::Deferred(_))
+                | Some(RamValue::Record(_)) => {
+                    interpreter.push_execution_token(name);
+                }
+                Some(RamValue::ExecutionToken(address)) => {
+                    interpreter.push_execution_token(*address);
                 }
                 None => {
                     return Err(("Unknown address".to_string(), interpreter.location()));
@@ -82,7 +258,7 @@ mod tests {
         let result = interpreter.evaluate("life @", None);
         assert!(result.is_err());
         let (err, _) = result.unwrap_err();
-        assert_eq!(err, "Address not found: life");
+        assert_eq!(err, "Undefined word: life");
     }
 
     #[test]
@@ -137,6 +313,25 @@ mod tests {
         assert_eq!(value, Value::Number(42.0));
     }
 
+    #[test]
+    fn set_on_word_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.seal_all();
+        interpreter.set_redefinition_policy(RedefinitionPolicy::Error);
+        let result = interpreter.evaluate("5 [ dup ] set", None);
+        assert_eq!(result.unwrap_err().0, "Unable to set word: dup");
+        interpreter.recover();
+        interpreter.stack.clear();
+
+        let result = interpreter.evaluate(": f \"\" \"\" \"\" 1 ;\n5 [ f ] set", None);
+        assert_eq!(result.unwrap_err().0, "Unable to set word: f");
+        interpreter.recover();
+        interpreter.stack.clear();
+
+        interpreter.evaluate("var hp 5 hp set 2 dup", None).unwrap();
+        assert_eq!(interpreter.stack, [2.0.into(), 2.0.into()]);
+    }
+
     #[test]
     fn get_returns_value_of_variable_number() {
         let mut interpreter = Interpreter::new(());
@@ -187,4 +382,205 @@ mod tests {
         let (err, _) = result.unwrap_err();
         assert_eq!(err, "Unknown address");
     }
+
+    #[test]
+    fn constant_pushes_value() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("42 constant answer answer answer +", None)
+            .unwrap();
+        assert_eq!(interpreter.stack, [84.0.into()]);
+    }
+
+    #[test]
+    fn constant_redefinition_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("42 constant answer 43 constant answer", None);
+        assert_eq!(result.unwrap_err().0, "Constant already defined: answer");
+    }
+
+    #[test]
+    fn constant_cannot_be_changed() {
+        for code in ["1 to answer", "1 [ answer ] set"] {
+            let mut interpreter = Interpreter::new(());
+            interpreter.evaluate("42 constant answer", None).unwrap();
+            let result = interpreter.evaluate(code, None);
+            assert_eq!(result.unwrap_err().0, "Unable to change constant: answer");
+        }
+
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("42 constant answer 1 value answer", None);
+        assert_eq!(result.unwrap_err().0, "Constant already defined: answer");
+    }
+
+    #[test]
+    fn value_pushes_value_and_to_changes_it() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("100 value hp hp 90 to hp hp", None)
+            .unwrap();
+        assert_eq!(interpreter.stack, [100.0.into(), 90.0.into()]);
+    }
+
+    #[test]
+    fn to_on_non_value_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("var life 1 to life", None);
+        assert_eq!(result.unwrap_err().0, "Not a value: life");
+    }
+
+    #[test]
+    fn value_in_compiled_word() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        0 value count
+        : increment "" "" "" count 1 + to count ;
+        increment increment count
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.stack, [2.0.into()]);
+    }
+
+    #[test]
+    fn get_builtin_pushes_execution_token() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("[ dup ] get", None).unwrap();
+        let dup = interpreter.get_address("dup");
+        assert_eq!(interpreter.stack, [StackValue::ExecutionToken(dup)]);
+    }
+
+    #[test]
+    fn get_compiled_pushes_execution_token_that_can_be_executed() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        : double "" "" "" 2 * ;
+        [ double ] get
+        21 swap @
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.stack, [42.0.into()]);
+    }
+
+    #[test]
+    fn execution_token_can_be_stored_in_variable() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        var action
+        [ dup ] get action set
+        3 action get @
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.stack, [3.0.into(), 3.0.into()]);
+    }
+
+    #[test]
+    fn execute_non_address_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1 @", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Expected an address or execution token"
+        );
+    }
+
+    #[test]
+    fn tick_pushes_execution_token_without_running() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("2 ' dup", None).unwrap();
+        let dup = interpreter.get_address("dup");
+        assert_eq!(
+            interpreter.stack,
+            [2.0.into(), StackValue::ExecutionToken(dup)]
+        );
+    }
+
+    #[test]
+    fn tick_unknown_word_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("' missing", None);
+        assert_eq!(result.unwrap_err().0, "Undefined word: missing");
+    }
+
+    #[test]
+    fn execute_runs_execution_token() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("2 ' dup execute", None).unwrap();
+        assert_eq!(interpreter.stack, [2.0.into(), 2.0.into()]);
+    }
+
+    #[test]
+    fn execute_dispatches_through_variable() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        : idle "" "" "" "idle" ;
+        : chase "" "" "" "chase" ;
+        var state
+        ' idle state set
+        state get execute
+        ' chase state set
+        state get execute
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "chase");
+        assert_eq!(interpreter.pop_string().unwrap(), "idle");
+    }
+
+    #[test]
+    fn execute_data_address_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("var life life execute", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Expected an execution token, got the data address @life"
+        );
+    }
+
+    #[test]
+    fn deferred_word_calls_target() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        defer greet
+        : hello "" "" "" "hello" ;
+        : run "" "" "" greet ;
+        ' hello is greet
+        run
+        ' dup is greet
+        run
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "hello");
+        assert_eq!(interpreter.pop_string().unwrap(), "hello");
+        assert!(interpreter.stack.is_empty());
+    }
+
+    #[test]
+    fn deferred_word_without_target_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("defer greet greet", None);
+        assert_eq!(result.unwrap_err().0, "Deferred word not set: greet");
+    }
+
+    #[test]
+    fn deferred_cycle_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("defer a ' a is a a", None);
+        assert_eq!(result.unwrap_err().0, "Deferred cycle: a calls itself");
+        interpreter.recover();
+
+        let code = "defer b defer c ' c is b ' b is c b";
+        let result = interpreter.evaluate(code, None);
+        assert_eq!(result.unwrap_err().0, "Deferred cycle: b calls itself");
+        interpreter.recover();
+
+        let code = "defer d defer e ' e is d ' dup is e 1 d";
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.stack, [1.0.into(), 1.0.into()]);
+    }
+
+    #[test]
+    fn is_on_non_deferred_word_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("' dup is drop", None);
+        assert_eq!(result.unwrap_err().0, "Not a deferred word: drop");
+    }
 }
@@ -0,0 +1,183 @@
+use super::*;
+
+/// Largest integer that numbers represent exactly, along with every integer below it.
+const MAX_EXACT_INTEGER: Number = 16_777_216.0;
+
+/// Pop a number that must be an integer for use in a bitwise operation.
+/// Only integers from -2^24 to 2^24 are accepted, as larger numbers may have been rounded.
+fn pop_bits<State>(interpreter: &mut Interpreter<State>) -> Result<i32, Err> {
+    let n = interpreter.pop_number()?;
+    if n.fract() != 0.0 || n.abs() > MAX_EXACT_INTEGER {
+        return Err((
+            format!("Bitwise operations require a 32 bit integer, got {}", n),
+            interpreter.location(),
+        ));
+    }
+    Ok(n as i32)
+}
+
+/// Pop a shift amount for a bitwise shift.
+fn pop_shift<State>(interpreter: &mut Interpreter<State>) -> Result<u32, Err> {
+    let shift = pop_bits(interpreter)?;
+    if !(0..32).contains(&shift) {
+        return Err((
+            format!("Shift must be in the range 0..32, got {}", shift),
+            interpreter.location(),
+        ));
+    }
+    Ok(shift as u32)
+}
+
+pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
+    interpreter.register_builtin(
+        "and",
+        "n n -- n",
+        "Bitwise and of two integers.",
+        "0b1100 0b1010 and",
+        |interpreter| {
+            let a = pop_bits(interpreter)?;
+            let b = pop_bits(interpreter)?;
+            interpreter.push_number((a & b) as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "or",
+        "n n -- n",
+        "Bitwise or of two integers.",
+        "0b1100 0b1010 or",
+        |interpreter| {
+            let a = pop_bits(interpreter)?;
+            let b = pop_bits(interpreter)?;
+            interpreter.push_number((a | b) as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "xor",
+        "n n -- n",
+        "Bitwise exclusive or of two integers.",
+        "0b1100 0b1010 xor",
+        |interpreter| {
+            let a = pop_bits(interpreter)?;
+            let b = pop_bits(interpreter)?;
+            interpreter.push_number((a ^ b) as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "invert",
+        "n -- n",
+        "Flips all bits of an integer.",
+        "0 invert",
+        |interpreter| {
+            let a = pop_bits(interpreter)?;
+            interpreter.push_number(!a as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "lshift",
+        "n shift -- n",
+        "Shifts the bits of an integer left.",
+        "1 4 lshift",
+        |interpreter| {
+            let shift = pop_shift(interpreter)?;
+            let a = pop_bits(interpreter)?;
+            interpreter.push_number(a.wrapping_shl(shift) as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "rshift",
+        "n shift -- n",
+        "Shifts the bits of an integer right. Zeros are shifted in.",
+        "16 4 rshift",
+        |interpreter| {
+            let shift = pop_shift(interpreter)?;
+            let a = pop_bits(interpreter)?;
+            interpreter.push_number(((a as u32) >> shift) as i32 as Number);
+            Ok(())
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper to evaluate code and pop the resulting number.
+    fn eval_number(code: &str) -> f32 {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate(code, None).unwrap();
+        interpreter.pop_number().unwrap()
+    }
+
+    #[test]
+    fn and_or_xor() {
+        assert_eq!(eval_number("0b1100 0b1010 and"), 0b1000 as f32);
+        assert_eq!(eval_number("0b1100 0b1010 or"), 0b1110 as f32);
+        assert_eq!(eval_number("0b1100 0b1010 xor"), 0b0110 as f32);
+    }
+
+    #[test]
+    fn invert() {
+        assert_eq!(eval_number("0 invert"), -1.0);
+        assert_eq!(eval_number("0xFF invert 0xFF and"), 0.0);
+    }
+
+    #[test]
+    fn shifts() {
+        assert_eq!(eval_number("1 4 lshift"), 16.0);
+        assert_eq!(eval_number("16 4 rshift"), 1.0);
+        assert_eq!(eval_number("-1 28 rshift"), 15.0);
+    }
+
+    #[test]
+    fn accepts_exact_integers() {
+        assert_eq!(eval_number("0x1000000 0 or"), 16777216.0);
+        assert_eq!(eval_number("-0x1000000 0xFF and"), 0.0);
+        assert_eq!(eval_number("0xFFFFFF 4 rshift"), 0xFFFFF as f32);
+    }
+
+    #[test]
+    fn rounded_integers_return_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("0x80000000 0 or", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Bitwise operations require a 32 bit integer, got 2147483600"
+        );
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("16777216 2 * 1 and", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Bitwise operations require a 32 bit integer, got 33554432"
+        );
+    }
+
+    #[test]
+    fn non_integer_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1.5 1 and", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Bitwise operations require a 32 bit integer, got 1.5"
+        );
+    }
+
+    #[test]
+    fn shift_out_of_range_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1 32 lshift", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Shift must be in the range 0..32, got 32"
+        );
+    }
+}
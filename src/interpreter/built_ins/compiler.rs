@@ -1,5 +1,46 @@
 use super::*;
 
+/// Find the `;` that ends the definition starting at the program counter.
+fn find_compile_end<State>(interpreter: &Interpreter<State>) -> Result<usize, Err> {
+    // Step through program and find the call of the end compile address
+    for i in interpreter.program_counter..interpreter.program.len() {
+        if let Instruction::Address(address) = interpreter.program[i] {
+            if address == interpreter.address_cache.compile_end {
+                return Ok(i);
+            }
+        }
+    }
+    Err((
+        "No ; found, unable to compile".into(),
+        interpreter.location(),
+    ))
+}
+
+/// Compile the instructions from the program counter up to the end of the definition.
+/// Immediate words are run now instead of being compiled, which lets them add to the word.
+fn compile_word<State>(
+    interpreter: &mut Interpreter<State>,
+    end_address_idx: usize,
+) -> Result<CompiledWord, Err> {
+    interpreter.compile_buffer = CompiledWord::default();
+    while interpreter.program_counter < end_address_idx {
+        let idx = interpreter.program_counter;
+        let instruction = interpreter.program[idx].clone();
+        let is_immediate = instruction
+            .get_address()
+            .is_some_and(|address| interpreter.immediate_words.contains(&address));
+
+        if is_immediate {
+            interpreter.execute_instruction(instruction)?;
+        } else {
+            let location = interpreter.location();
+            interpreter.compile_buffer.push(instruction, location);
+        }
+        interpreter.program_counter += 1;
+    }
+    Ok(std::mem::take(&mut interpreter.compile_buffer))
+}
+
 pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
     let example = ": squared \"squares a number\" \"n -- n\" \"2 squared\" * * ;";
     interpreter.register_builtin(
@@ -9,48 +50,27 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
             example,
             |interpreter| {
                 interpreter.compiling = true;
-                // Step through program and find the call of the end compile address
-                // Then iterate to get to that point and compile the function.
-                let mut end_address_idx = None;
-                for i in interpreter.program_counter..interpreter.program.len() {
-                    match interpreter.program[i] {
-                        Instruction::Address(address) => {
-                            if address == interpreter.address_cache.compile_end {
-                                end_address_idx = Some(i);
-                                break;
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-
-                let end_address_idx = match end_address_idx {
-                    Some(idx) => idx,
-                    None => return Err((
-                        "No ; found, unable to compile".into(),
-                        interpreter.location(),
-                    )),
-                };
+                let end_address_idx = find_compile_end(interpreter)?;
 
                 // Skip the ':'
                 interpreter.chomp_instruction()?;
+                let name_idx = interpreter.program_counter;
                 let name = interpreter.chomp_instruction()?.expect_address(interpreter)?;
                 let documentation = interpreter.chomp_instruction()?.expect_string(interpreter)?;
                 let stack_modification = interpreter.chomp_instruction()?.expect_string(interpreter)?;
                 let example = interpreter.chomp_instruction()?.expect_string(interpreter)?;
-                
-                interpreter.register_documentation(name, &stack_modification, &documentation, &example);
-                
-                // Compile the function
-                let mut idx = interpreter.program_counter;
-                let mut ops = Vec::new();
-                while idx < end_address_idx {
-                    ops.push(interpreter.program[idx].clone());
-                    idx += 1;
+
+                let word = compile_word(interpreter, end_address_idx)?;
+
+                // Report redefinitions at the name of the word.
+                interpreter.program_counter = name_idx;
+                if interpreter.coverage().is_some() {
+                    interpreter.cover_word(name, &word);
                 }
+                interpreter.define_word(name, RamValue::Compiled(word))?;
+                interpreter.register_documentation(name, &stack_modification, &documentation, &example);
+                interpreter.last_defined = Some(name);
 
-                interpreter.ram.insert(name, RamValue::Compiled(ops));
-                
                 // Reset compiler state and update program counter.
                 interpreter.program_counter = end_address_idx;
                 interpreter.compiling = false;
@@ -58,6 +78,76 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
             },
         );
 
+    interpreter.register_builtin(
+        "test:",
+        "test: $name .. ;",
+        "Defines a test. Tests are not run when defined, but by the test runner, each with an empty stack.",
+        "test: addition 1 2 + 3 assert-eq ;",
+        |interpreter| {
+            interpreter.compiling = true;
+            let end_address_idx = find_compile_end(interpreter)?;
+
+            // Skip the 'test:'
+            interpreter.chomp_instruction()?;
+            let name_idx = interpreter.program_counter;
+            let name = interpreter.chomp_instruction()?.expect_address(interpreter)?;
+            let name = interpreter.get_name(name);
+            let word = compile_word(interpreter, end_address_idx)?;
+
+            interpreter.program_counter = name_idx;
+            interpreter.define_test(name, word);
+
+            interpreter.program_counter = end_address_idx;
+            interpreter.compiling = false;
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "immediate",
+        "--",
+        "Marks the most recently defined word as immediate. Immediate words run while compiling a word instead of being compiled into it.",
+        ": unless \"\" \"\" \"\" postpone ! postpone if ; immediate",
+        |interpreter| {
+            let name = interpreter.last_defined.ok_or((
+                "No word defined to mark as immediate".to_string(),
+                interpreter.location(),
+            ))?;
+            interpreter.immediate_words.insert(name);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "postpone",
+        "postpone $name --",
+        "Adds $name to the word being compiled instead of running it. Used in immediate words to define new control structures.",
+        ": unless \"\" \"\" \"\" postpone ! postpone if ; immediate",
+        |interpreter| {
+            if !interpreter.compiling {
+                return Err((
+                    "postpone can only be used while compiling".to_string(),
+                    interpreter.location(),
+                ));
+            }
+
+            // Skip the 'postpone' and add the following instruction to the word being compiled.
+            interpreter.chomp_instruction()?;
+            let instruction = match interpreter.program.get(interpreter.program_counter) {
+                Some(instruction) => instruction.clone(),
+                None => {
+                    return Err((
+                        "Expected a word after postpone".to_string(),
+                        interpreter.location(),
+                    ))
+                }
+            };
+            let location = interpreter.location();
+            interpreter.compile_buffer.push(instruction, location);
+            Ok(())
+        },
+    );
+
     interpreter.register_builtin(
         ";",
         "",
@@ -98,4 +188,105 @@ mod tests {
 
         assert_eq!(4.0, interpreter.pop_number().unwrap());
     }
+
+    #[test]
+    fn infinite_recursion_returns_error() {
+        let code = r#"
+        : forever "recurses forever" "--" "forever" forever ;
+        forever
+        "#;
+
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate(code, None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Call stack overflow in forever, calls nested more than 256 deep"
+        );
+    }
+
+    #[test]
+    fn control_flow_works_in_compiled_words() {
+        let code = r#"
+        : describe "" "" "" 0 == if "zero" else "other" end ;
+        : count-to "" "" "" 0 begin 1 + dup 3 == if break end loop ;
+        0 describe 1 describe count-to
+        "#;
+
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(3.0, interpreter.pop_number().unwrap());
+        assert_eq!("other", interpreter.pop_string().unwrap());
+        assert_eq!("zero", interpreter.pop_string().unwrap());
+    }
+
+    #[test]
+    fn immediate_word_defines_control_structure() {
+        let code = r#"
+        : unless "n unless .. end" "Runs the code if n is false" "0 unless 1 end" postpone ! postpone if ; immediate
+        : check "" "" "" unless "falsy" else "truthy" end ;
+        0 check 1 check
+        "#;
+
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!("truthy", interpreter.pop_string().unwrap());
+        assert_eq!("falsy", interpreter.pop_string().unwrap());
+    }
+
+    #[test]
+    fn immediate_word_runs_while_compiling() {
+        let code = r#"
+        : compile-time "" "" "" "ran" ; immediate
+        : word "" "" "" compile-time 1 ;
+        "#;
+
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!("ran", interpreter.pop_string().unwrap());
+        assert!(interpreter.stack.is_empty());
+
+        interpreter.evaluate("word", None).unwrap();
+        assert_eq!(1.0, interpreter.pop_number().unwrap());
+        assert!(interpreter.stack.is_empty());
+    }
+
+    #[test]
+    fn postpone_outside_of_compiling_returns_error() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("postpone dup", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "postpone can only be used while compiling"
+        );
+    }
+
+    #[test]
+    fn immediate_without_definition_returns_error() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("immediate", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "No word defined to mark as immediate"
+        );
+    }
+
+    #[test]
+    fn test_definitions_are_not_run() {
+        let code = "test: adds 1 2 + 3 assert-eq ;\ntest: fails 1 2 assert-eq ;";
+
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate(code, None).unwrap();
+        assert!(interpreter.stack.is_empty());
+        assert_eq!(interpreter.test_names(), ["adds", "fails"]);
+    }
+
+    #[test]
+    fn errors_in_compiled_words_report_their_location() {
+        let code = ": fails \"\" \"\" \"\"\n  0 1 / ;\nfails";
+
+        let mut interpreter = Interpreter::new(());
+        let (err, location) = interpreter.evaluate(code, None).unwrap_err();
+        assert_eq!(err, "Division by zero");
+        assert_eq!(location, (2, 7).into());
+    }
 }
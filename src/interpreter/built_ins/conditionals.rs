@@ -30,6 +30,9 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
         "1 1 == if 1 end",
         |interpreter| {
             let value = interpreter.pop_bool()?;
+            if interpreter.coverage().is_some() {
+                interpreter.cover_branch(value);
+            }
 
             // Get the end and else addresses
             let end_address = interpreter.address_cache.end_statement;
@@ -70,15 +73,21 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
 
                 // Execute the code
                 for i in pc..end_pc {
-                    interpreter.execute_instruction(interpreter.program[i].clone())?;
-                    interpreter.program_counter += 1;
+                    interpreter.program_counter = i;
+                    interpreter.step_instruction(interpreter.program[i].clone())?;
+                    if interpreter.is_unwinding() {
+                        return Ok(());
+                    }
                 }
             } else if let Some(else_pc) = else_pc {
                 // We're using an else statement, so skip the truthy part
                 // of the if statement
                 for i in (else_pc + 1)..end_pc {
-                    interpreter.execute_instruction(interpreter.program[i].clone())?;
-                    interpreter.program_counter += 1;
+                    interpreter.program_counter = i;
+                    interpreter.step_instruction(interpreter.program[i].clone())?;
+                    if interpreter.is_unwinding() {
+                        return Ok(());
+                    }
                 }
             }
 
@@ -135,7 +144,7 @@ mod tests {
         "#;
         let result = interpreter.evaluate(code, None);
         assert_eq!(result, Ok(()));
-        assert_eq!(true, interpreter.stack.is_empty());
+        assert!(interpreter.stack.is_empty());
     }
 
     #[test]
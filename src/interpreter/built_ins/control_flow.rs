@@ -13,10 +13,7 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
             }
 
             if interpreter.program_counter_stack.is_empty() {
-                return Err((
-                    "'begin' statement not found".into(),
-                    interpreter.location(),
-                ));
+                return Err(("'begin' statement not found".into(), interpreter.location()));
             }
 
             let begin_pc = interpreter.program_counter_stack.pop().unwrap();
@@ -47,10 +44,7 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
                 interpreter.break_loop = true;
             }
             None => {
-                return Err((
-                    "'loop' statement not found".into(),
-                    interpreter.location(),
-                ));
+                return Err(("'loop' statement not found".into(), interpreter.location()));
             }
         }
 
@@ -85,10 +79,7 @@ mod tests {
         let result = interpreter.evaluate(code, None);
         assert_eq!(
             result,
-            Err((
-                "'begin' statement not found".into(),
-                interpreter.location()
-            ))
+            Err(("'begin' statement not found".into(), interpreter.location()))
         );
     }
 
@@ -124,10 +115,7 @@ mod tests {
         let result = interpreter.evaluate(code, None);
         assert_eq!(
             result,
-            Err((
-                "'loop' statement not found".into(),
-                interpreter.location()
-            ))
+            Err(("'loop' statement not found".into(), interpreter.location()))
         );
     }
 
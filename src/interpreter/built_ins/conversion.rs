@@ -0,0 +1,247 @@
+use super::*;
+
+/// Largest precision accepted by `format-number`.
+const MAX_PRECISION: usize = 100;
+
+/// Splits a template into literal segments around each `{}` placeholder.
+/// `{{` and `}}` are escapes for literal braces.
+fn parse_template(template: &str) -> Result<Vec<String>, String> {
+    let mut segments = vec![String::new()];
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                segments.last_mut().unwrap().push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                segments.push(String::new());
+            }
+            ('{', _) | ('}', _) => {
+                return Err(format!(
+                    "Invalid format template, unmatched '{}' in \"{}\"",
+                    c, template
+                ));
+            }
+            (c, _) => segments.last_mut().unwrap().push(c),
+        }
+    }
+    Ok(segments)
+}
+
+pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
+    interpreter.register_builtin(
+        ">string",
+        "any -- string",
+        "Converts a value to a string. Numbers are displayed as in 'print-stack'.",
+        "42 >string",
+        |interpreter| {
+            let value = interpreter.pop()?;
+            let string = interpreter.display_value(&value);
+            interpreter.push_string(string);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        ">number",
+        "string -- n",
+        "Parses a string as a number. Returns an error if the string is not a number.",
+        "\"3.5\" >number",
+        |interpreter| {
+            let string = interpreter.pop_string()?;
+            match parse_number(string.trim()) {
+                Some(number) => {
+                    interpreter.push_number(number);
+                    Ok(())
+                }
+                None => Err((
+                    format!("Unable to parse {} as a number", escape_string(&string)),
+                    interpreter.location(),
+                )),
+            }
+        },
+    );
+
+    interpreter.register_builtin(
+        ">number?",
+        "string -- n 1 | string 0",
+        "Tries to parse a string as a number. Pushes the number and 1 on success, otherwise the original string and 0.",
+        "\"3.5\" >number?",
+        |interpreter| {
+            let string = interpreter.pop_string()?;
+            match parse_number(string.trim()) {
+                Some(number) => {
+                    interpreter.push_number(number);
+                    interpreter.push_number(1.0);
+                }
+                None => {
+                    interpreter.push_string(string);
+                    interpreter.push_number(0.0);
+                }
+            }
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "format-number",
+        "n precision -- string",
+        "Converts a number to a string with the given number of decimal places.",
+        "3.14159 2 format-number",
+        |interpreter| {
+            let precision = interpreter.pop_number()?;
+            let number = interpreter.pop_number()?;
+            if precision < 0.0 || precision.fract() != 0.0 {
+                return Err((
+                    format!(
+                        "precision must be a non-negative integer, got {}",
+                        precision
+                    ),
+                    interpreter.location(),
+                ));
+            }
+            if precision > MAX_PRECISION as Number {
+                return Err((
+                    format!(
+                        "precision must be at most {}, got {}",
+                        MAX_PRECISION, precision
+                    ),
+                    interpreter.location(),
+                ));
+            }
+            interpreter.push_string(format!("{:.*}", precision as usize, number));
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "format",
+        "any.. template -- string",
+        "Replaces each '{}' in the template with a value from the stack, in the order they were pushed. Use '{{' and '}}' for literal braces.",
+        "10 20 \"HP: {} / {}\" format",
+        |interpreter| {
+            let template = interpreter.pop_string()?;
+            let segments = parse_template(&template)
+                .map_err(|err| (err, interpreter.location()))?;
+
+            let placeholders = segments.len() - 1;
+            if placeholders > interpreter.stack.len() {
+                return Err((
+                    format!(
+                        "Format template expects {} values, but the stack has {}",
+                        placeholders,
+                        interpreter.stack.len()
+                    ),
+                    interpreter.location(),
+                ));
+            }
+
+            let values = interpreter
+                .stack
+                .split_off(interpreter.stack.len() - placeholders);
+
+            let mut string = segments[0].clone();
+            for (value, segment) in values.iter().zip(segments.iter().skip(1)) {
+                string.push_str(&interpreter.display_value(value));
+                string.push_str(segment);
+            }
+
+            interpreter.push_string(string);
+            Ok(())
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_string_matches_print_stack() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("42 >string 0.5 >string \"hi\" >string", None)
+            .unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "hi");
+        assert_eq!(interpreter.pop_string().unwrap(), "0.5");
+        assert_eq!(interpreter.pop_string().unwrap(), "42");
+    }
+
+    #[test]
+    fn to_number_parses_number() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("\" 3.5 \" >number", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 3.5);
+    }
+
+    #[test]
+    fn to_number_returns_err_on_bad_input() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("\"abc\" >number", None);
+        assert_eq!(result.unwrap_err().0, "Unable to parse \"abc\" as a number");
+    }
+
+    #[test]
+    fn try_to_number_pushes_flag() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("\"12\" >number? \"abc\" >number?", None)
+            .unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 0.0);
+        assert_eq!(interpreter.pop_string().unwrap(), "abc");
+        assert_eq!(interpreter.pop_number().unwrap(), 1.0);
+        assert_eq!(interpreter.pop_number().unwrap(), 12.0);
+    }
+
+    #[test]
+    fn format_number_uses_precision() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("3.14159 2 format-number 2 0 format-number", None)
+            .unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "2");
+        assert_eq!(interpreter.pop_string().unwrap(), "3.14");
+    }
+
+    #[test]
+    fn format_number_negative_precision_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1 -1 format-number", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "precision must be a non-negative integer, got -1"
+        );
+    }
+
+    #[test]
+    fn format_interpolates_values_in_order() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("1 10 20 \"HP: {} / {} {{ok}}\" format", None)
+            .unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "HP: 10 / 20 {ok}");
+        assert_eq!(interpreter.pop_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn format_with_too_few_values_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1 \"{} {}\" format", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Format template expects 2 values, but the stack has 1"
+        );
+    }
+
+    #[test]
+    fn format_with_unmatched_brace_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1 \"{ }\" format", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Invalid format template, unmatched '{' in \"{ }\""
+        );
+    }
+}
@@ -81,6 +81,260 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
             Ok(())
         },
     );
+
+    interpreter.register_builtin(
+        "pow",
+        "n n -- n",
+        "Raises the second number to the power of the first.",
+        "2 3 pow",
+        |interpreter| {
+            let exponent = interpreter.pop_number()?;
+            let base = interpreter.pop_number()?;
+            let result = base.powf(exponent);
+            if result.is_nan() {
+                return Err((
+                    format!("Invalid power, {} to the power of {}", base, exponent),
+                    interpreter.location(),
+                ));
+            }
+            interpreter.push_number(result);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "sqrt",
+        "n -- n",
+        "Square root of a number.",
+        "9 sqrt",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            if a < 0.0 {
+                return Err((
+                    "Square root of a negative number".to_string(),
+                    interpreter.location(),
+                ));
+            }
+            interpreter.push_number(a.sqrt());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "sin",
+        "n -- n",
+        "Sine of an angle in radians.",
+        "2 pi / sin",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            interpreter.push_number(a.sin());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "cos",
+        "n -- n",
+        "Cosine of an angle in radians.",
+        "pi cos",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            interpreter.push_number(a.cos());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "tan",
+        "n -- n",
+        "Tangent of an angle in radians.",
+        "4 pi / tan",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            interpreter.push_number(a.tan());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "atan2",
+        "y x -- n",
+        "Angle in radians between the positive x axis and the point (x, y).",
+        "1 1 atan2",
+        |interpreter| {
+            let x = interpreter.pop_number()?;
+            let y = interpreter.pop_number()?;
+            interpreter.push_number(y.atan2(x));
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "abs",
+        "n -- n",
+        "Absolute value of a number.",
+        "-3 abs",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            interpreter.push_number(a.abs());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "neg",
+        "n -- n",
+        "Negates a number.",
+        "3 neg",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            interpreter.push_number(-a);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "min",
+        "n n -- n",
+        "Smallest of two numbers.",
+        "1 2 min",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            let b = interpreter.pop_number()?;
+            interpreter.push_number(a.min(b));
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "max",
+        "n n -- n",
+        "Largest of two numbers.",
+        "1 2 max",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            let b = interpreter.pop_number()?;
+            interpreter.push_number(a.max(b));
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "floor",
+        "n -- n",
+        "Rounds a number down.",
+        "1.7 floor",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            interpreter.push_number(a.floor());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "ceil",
+        "n -- n",
+        "Rounds a number up.",
+        "1.2 ceil",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            interpreter.push_number(a.ceil());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "round",
+        "n -- n",
+        "Rounds a number to the nearest integer. Halfway cases are rounded away from zero.",
+        "1.5 round",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            interpreter.push_number(a.round());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "clamp",
+        "n min max -- n",
+        "Restricts a number to the range min..max.",
+        "15 0 10 clamp",
+        |interpreter| {
+            let max = interpreter.pop_number()?;
+            let min = interpreter.pop_number()?;
+            let a = interpreter.pop_number()?;
+            if min.is_nan() || max.is_nan() {
+                return Err((
+                    format!("Invalid range, min {} and max {} must be numbers", min, max),
+                    interpreter.location(),
+                ));
+            }
+            if min > max {
+                return Err((
+                    format!("Invalid range, min {} is greater than max {}", min, max),
+                    interpreter.location(),
+                ));
+            }
+            interpreter.push_number(a.clamp(min, max));
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "lerp",
+        "a b t -- n",
+        "Linearly interpolates between a and b by t.",
+        "0 10 0.5 lerp",
+        |interpreter| {
+            let t = interpreter.pop_number()?;
+            let b = interpreter.pop_number()?;
+            let a = interpreter.pop_number()?;
+            interpreter.push_number(a + (b - a) * t);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "sign",
+        "n -- -1|0|1",
+        "Sign of a number. Puts -1 on the stack if it is negative, 1 if it is positive and 0 otherwise.",
+        "-3 sign",
+        |interpreter| {
+            let a = interpreter.pop_number()?;
+            let sign = if a > 0.0 {
+                1.0
+            } else if a < 0.0 {
+                -1.0
+            } else {
+                0.0
+            };
+            interpreter.push_number(sign);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "pi",
+        "-- n",
+        "Pushes pi onto the stack.",
+        "pi",
+        |interpreter| {
+            interpreter.push_number(std::f32::consts::PI);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "tau",
+        "-- n",
+        "Pushes tau (2 * pi) onto the stack.",
+        "tau",
+        |interpreter| {
+            interpreter.push_number(std::f32::consts::TAU);
+            Ok(())
+        },
+    );
 }
 
 #[cfg(test)]
@@ -139,4 +393,113 @@ mod tests {
             assert_eq!(msg, "Division by zero");
         }
     }
+
+    /// Helper to evaluate code and pop the resulting number.
+    fn eval_number(code: &str) -> f32 {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate(code, None).unwrap();
+        interpreter.pop_number().unwrap()
+    }
+
+    /// Helper to evaluate code and return the error message.
+    fn eval_err(code: &str) -> String {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate(code, None).unwrap_err().0
+    }
+
+    #[test]
+    fn pow() {
+        assert_eq!(eval_number("2 3 pow"), 8.0);
+        assert_eq!(eval_number("4 0.5 pow"), 2.0);
+    }
+
+    #[test]
+    fn pow_invalid_returns_err() {
+        assert_eq!(
+            eval_err("-8 0.5 pow"),
+            "Invalid power, -8 to the power of 0.5"
+        );
+    }
+
+    #[test]
+    fn sqrt() {
+        assert_eq!(eval_number("9 sqrt"), 3.0);
+    }
+
+    #[test]
+    fn sqrt_negative_returns_err() {
+        assert_eq!(eval_err("-1 sqrt"), "Square root of a negative number");
+    }
+
+    #[test]
+    fn trig() {
+        assert!((eval_number("2 pi / sin") - 1.0).abs() < 1e-6);
+        assert!((eval_number("pi cos") + 1.0).abs() < 1e-6);
+        assert!((eval_number("4 pi / tan") - 1.0).abs() < 1e-6);
+        assert!((eval_number("1 0 atan2") - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn abs_and_neg() {
+        assert_eq!(eval_number("-3 abs"), 3.0);
+        assert_eq!(eval_number("3 neg"), -3.0);
+    }
+
+    #[test]
+    fn min_and_max() {
+        assert_eq!(eval_number("1 2 min"), 1.0);
+        assert_eq!(eval_number("1 2 max"), 2.0);
+    }
+
+    #[test]
+    fn floor_ceil_round() {
+        assert_eq!(eval_number("1.7 floor"), 1.0);
+        assert_eq!(eval_number("-1.2 floor"), -2.0);
+        assert_eq!(eval_number("1.2 ceil"), 2.0);
+        assert_eq!(eval_number("1.5 round"), 2.0);
+        assert_eq!(eval_number("-1.5 round"), -2.0);
+    }
+
+    #[test]
+    fn clamp() {
+        assert_eq!(eval_number("15 0 10 clamp"), 10.0);
+        assert_eq!(eval_number("-5 0 10 clamp"), 0.0);
+        assert_eq!(eval_number("5 0 10 clamp"), 5.0);
+    }
+
+    #[test]
+    fn clamp_invalid_range_returns_err() {
+        assert_eq!(
+            eval_err("5 10 0 clamp"),
+            "Invalid range, min 10 is greater than max 0"
+        );
+        assert_eq!(
+            eval_err("5 NaN 10 clamp"),
+            "Invalid range, min NaN and max 10 must be numbers"
+        );
+        assert_eq!(
+            eval_err("5 0 NaN clamp"),
+            "Invalid range, min 0 and max NaN must be numbers"
+        );
+    }
+
+    #[test]
+    fn lerp() {
+        assert_eq!(eval_number("0 10 0.5 lerp"), 5.0);
+        assert_eq!(eval_number("10 20 0 lerp"), 10.0);
+        assert_eq!(eval_number("10 20 1 lerp"), 20.0);
+    }
+
+    #[test]
+    fn sign() {
+        assert_eq!(eval_number("-3 sign"), -1.0);
+        assert_eq!(eval_number("0 sign"), 0.0);
+        assert_eq!(eval_number("0.1 sign"), 1.0);
+    }
+
+    #[test]
+    fn pi_and_tau() {
+        assert_eq!(eval_number("pi"), std::f32::consts::PI);
+        assert_eq!(eval_number("tau"), std::f32::consts::TAU);
+    }
 }
@@ -1,25 +1,37 @@
 /// Definitions for addressing words.
 mod addressing;
+/// Definitions for bitwise words.
+mod bitwise;
 /// Definitions for compiling words.
 mod compiler;
 /// Definitions for conditional words.
 mod conditionals;
 /// Definitions for control flow words.
 mod control_flow;
+/// Definitions for conversion words.
+mod conversion;
 /// Definitions for equality words.
 mod equality;
 /// Definitions for logic words.
 mod logic;
 /// Definitions for math words.
 mod math;
+/// Definitions for random number words.
+mod random;
 /// Definitions for read mode words.
 mod read_mode;
+/// Definitions for record words.
+mod record;
 /// Definitions for runtime words.
 mod runtime;
 /// Definitions for stack words.
 mod stack_ops;
 /// Definitions for string words.
 mod string;
+/// Definitions for testing words.
+mod testing;
+/// Definitions for type inspection words.
+mod types;
 
 use super::*;
 
@@ -40,5 +52,11 @@ impl<State> Interpreter<State> {
         control_flow::register_builtins(self);
         math::register_builtins(self);
         string::register_builtins(self);
+        conversion::register_builtins(self);
+        random::register_builtins(self);
+        bitwise::register_builtins(self);
+        record::register_builtins(self);
+        types::register_builtins(self);
+        testing::register_builtins(self);
     }
 }
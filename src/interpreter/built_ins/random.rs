@@ -0,0 +1,148 @@
+use super::*;
+
+/// Pop a number that must be an integer.
+fn pop_integer<State>(interpreter: &mut Interpreter<State>, name: &str) -> Result<i64, Err> {
+    let n = interpreter.pop_number()?;
+    if n.fract() != 0.0 {
+        return Err((
+            format!("{} must be an integer, got {}", name, n),
+            interpreter.location(),
+        ));
+    }
+    Ok(n as i64)
+}
+
+pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
+    interpreter.register_builtin(
+        "rand",
+        "-- n",
+        "Pushes a random number in the range 0..1. Uses the seeded random number generator of the interpreter.",
+        "rand",
+        |interpreter| {
+            let n = interpreter.rng.next_f32();
+            interpreter.push_number(n);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "rand-int",
+        "min max -- n",
+        "Pushes a random integer between min and max, inclusive.",
+        "1 6 rand-int",
+        |interpreter| {
+            let max = pop_integer(interpreter, "max")?;
+            let min = pop_integer(interpreter, "min")?;
+            if min > max {
+                return Err((
+                    format!("Invalid range, min {} is greater than max {}", min, max),
+                    interpreter.location(),
+                ));
+            }
+
+            let span = (max as i128 - min as i128 + 1).min(u64::MAX as i128) as u64;
+            let n = min as i128 + interpreter.rng.next_below(span) as i128;
+            interpreter.push_number(n as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "rand-choice",
+        "any.. n -- any",
+        "Pops n items and pushes one of them, chosen at random.",
+        "\"rock\" \"paper\" \"scissors\" 3 rand-choice",
+        |interpreter| {
+            let n = pop_integer(interpreter, "n")?;
+            if n <= 0 {
+                return Err((
+                    format!("n must be greater than 0, got {}", n),
+                    interpreter.location(),
+                ));
+            }
+            let n = n as usize;
+            if n > interpreter.stack.len() {
+                return Err((
+                    format!("n is greater than the stack size, got {}", n),
+                    interpreter.location(),
+                ));
+            }
+
+            let mut items = interpreter.stack.split_off(interpreter.stack.len() - n);
+            let choice = interpreter.rng.next_below(n as u64) as usize;
+            interpreter.stack.push(items.swap_remove(choice));
+            Ok(())
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rand_is_deterministic_for_seed() {
+        let mut a = Interpreter::new(());
+        let mut b = Interpreter::new(());
+        a.set_seed(1234);
+        b.set_seed(1234);
+        a.evaluate("rand rand 1 100 rand-int", None).unwrap();
+        b.evaluate("rand rand 1 100 rand-int", None).unwrap();
+        assert_eq!(a.stack, b.stack);
+    }
+
+    #[test]
+    fn rand_state_can_be_restored() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("rand drop", None).unwrap();
+        let state = interpreter.rng_state();
+        interpreter.evaluate("rand", None).unwrap();
+        let expected = interpreter.pop_number().unwrap();
+
+        interpreter.set_rng_state(state);
+        interpreter.evaluate("rand", None).unwrap();
+        assert_eq!(expected, interpreter.pop_number().unwrap());
+    }
+
+    #[test]
+    fn rand_int_is_in_range() {
+        let mut interpreter = Interpreter::new(());
+        for _ in 0..100 {
+            interpreter.evaluate("-2 2 rand-int", None).unwrap();
+            let n = interpreter.pop_number().unwrap();
+            assert!((-2.0..=2.0).contains(&n));
+            assert_eq!(n.fract(), 0.0);
+        }
+    }
+
+    #[test]
+    fn rand_int_invalid_range_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("6 1 rand-int", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Invalid range, min 6 is greater than max 1"
+        );
+    }
+
+    #[test]
+    fn rand_choice_picks_one_item() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("0 \"a\" \"b\" \"c\" 3 rand-choice", None)
+            .unwrap();
+        let choice = interpreter.pop_string().unwrap();
+        assert!(["a", "b", "c"].contains(&choice.as_str()));
+        assert_eq!(interpreter.stack, [0.0.into()]);
+    }
+
+    #[test]
+    fn rand_choice_with_too_few_items_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("\"a\" 2 rand-choice", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "n is greater than the stack size, got 2"
+        );
+    }
+}
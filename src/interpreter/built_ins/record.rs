@@ -0,0 +1,188 @@
+use super::*;
+
+pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
+    interpreter.register_builtin(
+        "struct",
+        "struct $name { $field.. } --",
+        "Declares a record type. Defines make-$name to create a record, $name.$field to get a field, $name.$field! to set a field and $name? to check the type.",
+        "struct enemy { hp name } \t 10 \"slime\" make-enemy enemy.hp",
+        |interpreter| {
+            let open = interpreter.get_address("{");
+            let close = interpreter.get_address("}");
+
+            // Skip the 'struct'
+            interpreter.chomp_instruction()?;
+            let name_idx = interpreter.program_counter;
+            let name = interpreter.chomp_instruction()?.expect_address(interpreter)?;
+            if interpreter.chomp_instruction()?.expect_address(interpreter)? != open {
+                return Err((
+                    "Expected { after the struct name".to_string(),
+                    interpreter.location(),
+                ));
+            }
+
+            let mut fields: Vec<String> = vec![];
+            loop {
+                if interpreter.program_counter >= interpreter.program.len() {
+                    return Err((
+                        "No } found, unable to declare struct".to_string(),
+                        interpreter.location(),
+                    ));
+                }
+                let field = interpreter.chomp_instruction()?.expect_address(interpreter)?;
+                if field == close {
+                    break;
+                }
+                let field = interpreter.get_name(field);
+                if fields.contains(&field) {
+                    interpreter.program_counter -= 1;
+                    return Err((format!("Duplicate field: {}", field), interpreter.location()));
+                }
+                fields.push(field);
+            }
+            let end_idx = interpreter.program_counter - 1;
+
+            // Report redefinitions at the name of the struct.
+            interpreter.program_counter = name_idx;
+            let name = interpreter.get_name(name);
+            interpreter.define_struct(&name, fields)?;
+
+            interpreter.program_counter = end_idx;
+            Ok(())
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn struct_defines_constructor_and_accessors() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        struct enemy { hp name pos }
+        10 "slime" 3 make-enemy
+        dup enemy.hp
+        swap enemy.name
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "slime");
+        assert_eq!(interpreter.pop_number().unwrap(), 10.0);
+    }
+
+    #[test]
+    fn setter_returns_updated_record() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        struct enemy { hp name }
+        10 "slime" make-enemy 4 enemy.hp!
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        let record = interpreter.pop_record().unwrap();
+        assert_eq!(record.record_type().name(), "enemy");
+        assert_eq!(record.get("hp"), Some(&4.0.into()));
+        assert_eq!(
+            record.get("name"),
+            Some(&Value::String("slime".into()).into())
+        );
+    }
+
+    #[test]
+    fn type_predicate() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        struct enemy { hp }
+        struct item { hp }
+        1 make-enemy enemy?
+        1 make-item enemy?
+        1 enemy?
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.stack, [1.0.into(), 0.0.into(), 0.0.into()]);
+    }
+
+    #[test]
+    fn accessor_on_wrong_type_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let code = "struct enemy { hp } struct item { hp } 1 make-item enemy.hp";
+        let result = interpreter.evaluate(code, None);
+        assert_eq!(result.unwrap_err().0, "Expected record of type enemy");
+    }
+
+    #[test]
+    fn make_with_too_few_values_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("struct pos { x y } 1 make-pos", None);
+        assert_eq!(result.unwrap_err().0, "make-pos expects 2 values, got 1");
+    }
+
+    #[test]
+    fn duplicate_field_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("struct pos { x x }", None);
+        assert_eq!(result.unwrap_err().0, "Duplicate field: x");
+    }
+
+    #[test]
+    fn rejected_struct_leaves_dictionary_unchanged() {
+        let cases = [
+            ("42 constant pos.y", "Constant already defined: pos.y"),
+            (
+                ": pos.y! \"\" \"\" \"\" ;",
+                "Unable to redefine existing word: pos.y!",
+            ),
+        ];
+        for (existing, error) in cases {
+            let mut interpreter = Interpreter::new(());
+            interpreter.evaluate(existing, None).unwrap();
+            interpreter.set_redefinition_policy(RedefinitionPolicy::Error);
+            let words: Vec<String> = interpreter
+                .word_names()
+                .into_iter()
+                .map(String::from)
+                .collect();
+
+            let result = interpreter.evaluate("struct pos { x y }", None);
+            assert_eq!(result.unwrap_err().0, error);
+            assert_eq!(interpreter.word_names(), words);
+        }
+    }
+
+    #[test]
+    fn struct_named_like_built_in_type_returns_err() {
+        for name in ["number", "string", "address", "xt"] {
+            let mut interpreter = Interpreter::new(());
+            let result = interpreter.evaluate(&format!("struct {} {{ a }}", name), None);
+            assert_eq!(
+                result.unwrap_err().0,
+                format!("Struct name is a built-in type: {}", name)
+            );
+            let make = format!("make-{}", name);
+            assert!(!interpreter.word_names().contains(&make.as_str()));
+        }
+    }
+
+    #[test]
+    fn records_are_printed_with_field_names() {
+        let mut interpreter = Interpreter::new(());
+        let code = "struct enemy { hp name } 10 \"slime\" make-enemy";
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(
+            interpreter.stringify_value(&interpreter.stack[0]),
+            "enemy { hp: 10 name: \"slime\" }"
+        );
+    }
+
+    #[test]
+    fn struct_in_compiled_word() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        : declare "" "" "" struct pos { x y } ;
+        declare
+        1 2 make-pos pos.y
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 2.0);
+    }
+}
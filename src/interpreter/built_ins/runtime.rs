@@ -16,38 +16,98 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
         "",
         |interpreter| {
             let program = interpreter.stringify_program();
-            println!("{}", program);
-            Ok(())
+            interpreter.write_output(&format!("{}\n", program))
         },
     );
 
     interpreter.register_builtin("print-stack", "", "Prints the stack.", "", |interpreter| {
-        let mut buffer = String::new();
-        buffer.push_str("[ ");
-        for value in interpreter.stack.iter() {
-            match value {
-                StackValue::Address(address) => {
-                    buffer.push_str(&interpreter.get_name(*address));
-                }
-                StackValue::Value(value) => match value {
-                    Value::Number(f) => buffer.push_str(&format!("{}", f)),
-                    Value::String(s) => buffer.push_str(&format!("\"{}\"", s)),
-                },
-            }
-            buffer.push_str(" ");
-        }
+        let stack = interpreter.stringify_stack();
+        interpreter.write_output(&format!("{}\n", stack))
+    });
 
-        buffer.push(']');
+    interpreter.register_builtin(
+        "print",
+        "any --",
+        "Prints a value. Strings are printed as is, other values as in 'print-stack'.",
+        "\"hello\" print",
+        |interpreter| {
+            let value = interpreter.pop()?;
+            let text = interpreter.display_value(&value);
+            interpreter.write_output(&text)
+        },
+    );
 
-        println!("{}", buffer);
+    interpreter.register_builtin(
+        "emit",
+        "n --",
+        "Prints the character with the given unicode code point.",
+        "65 emit",
+        |interpreter| {
+            let n = interpreter.pop_number()?;
+            let c = (n.fract() == 0.0 && n >= 0.0)
+                .then(|| char::from_u32(n as u32))
+                .flatten()
+                .ok_or((
+                    format!("Invalid character code: {}", n),
+                    interpreter.location(),
+                ))?;
+            interpreter.write_output(&c.to_string())
+        },
+    );
 
-        Ok(())
-    });
+    interpreter.register_builtin(
+        "cr",
+        "--",
+        "Prints a newline.",
+        "\"hello\" print cr",
+        |interpreter| interpreter.write_output("\n"),
+    );
 
-    interpreter.register_builtin("exit", "", "Exit the program.", "", |interpreter| {
-        interpreter.exit = true;
-        Ok(())
-    });
+    interpreter.register_builtin(
+        "exit",
+        "--",
+        "Exits the program immediately with status code 0.",
+        "exit",
+        |interpreter| {
+            interpreter.exit_code = Some(0);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "exit-with",
+        "n --",
+        "Exits the program immediately with status code n.",
+        "1 exit-with",
+        |interpreter| {
+            let code = interpreter.pop_number()?;
+            if code.fract() != 0.0 || code < i32::MIN as Number || code > i32::MAX as Number {
+                return Err((
+                    format!("Status code must be an integer, got {}", code),
+                    interpreter.location(),
+                ));
+            }
+            interpreter.exit_code = Some(code as i32);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "return",
+        "--",
+        "Returns from the current compiled word immediately.",
+        ": check \"\" \"\" \"\" 0 == if \"zero\" return end \"not zero\" ;",
+        |interpreter| {
+            if interpreter.frames.is_empty() {
+                return Err((
+                    "Unable to return outside of a word".to_string(),
+                    interpreter.location(),
+                ));
+            }
+            interpreter.program_counter = interpreter.program.len();
+            Ok(())
+        },
+    );
 
     // TODO: unable to start repl from interpreter.
     // There's a bug with the program counter/evaluate code.
@@ -67,9 +127,8 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
         "Show documentation for all words",
         "",
         |interpreter| {
-            interpreter.print_documentation();
-
-            Ok(())
+            let documentation = interpreter.documentation();
+            interpreter.write_output(&documentation)
         },
     );
 }
@@ -93,6 +152,134 @@ mod tests {
         let mut interpreter = Interpreter::new(());
         register_builtins(&mut interpreter);
         interpreter.evaluate("exit", None).unwrap();
-        assert!(interpreter.exit);
+        assert!(interpreter.exit());
+        assert_eq!(interpreter.exit_code(), Some(0));
+    }
+
+    #[test]
+    fn exit_stops_evaluation() {
+        let mut interpreter = Interpreter::new(());
+        let code = ": quit \"\" \"\" \"\" 1 3 exit-with 2 ; quit 4";
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.exit_code(), Some(3));
+        assert_eq!(interpreter.stack, [1.0.into()]);
+    }
+
+    #[test]
+    fn evaluation_works_again_after_exit() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("1 exit 2", None).unwrap();
+        interpreter.evaluate("3", None).unwrap();
+        assert_eq!(interpreter.exit_code(), None);
+        assert_eq!(interpreter.stack, [1.0.into(), 3.0.into()]);
+
+        interpreter.evaluate("4 exit-with", None).unwrap();
+        assert_eq!(interpreter.exit_code(), Some(4));
+        interpreter.clear_exit();
+        assert!(!interpreter.exit());
+    }
+
+    #[test]
+    fn exit_in_loop_stops_evaluation() {
+        let mut interpreter = Interpreter::new(());
+        let code = "0 begin 1 + dup 3 == if exit end loop";
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.stack, [3.0.into()]);
+    }
+
+    #[test]
+    fn exit_with_fractional_code_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1.5 exit-with", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Status code must be an integer, got 1.5"
+        );
+        assert!(!interpreter.exit());
+    }
+
+    #[test]
+    fn return_unwinds_current_word_only() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        : check "" "" "" 0 == if "zero" return end "not zero" ;
+        : run "" "" "" check "after" ;
+        0 run 1 run
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "after");
+        assert_eq!(interpreter.pop_string().unwrap(), "not zero");
+        assert_eq!(interpreter.pop_string().unwrap(), "after");
+        assert_eq!(interpreter.pop_string().unwrap(), "zero");
+        assert!(interpreter.stack.is_empty());
+    }
+
+    #[test]
+    fn return_in_loop_unwinds_word() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        : count "" "" "" 0 begin 1 + dup 5 == if return end loop ;
+        count 10
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.stack, [5.0.into(), 10.0.into()]);
+    }
+
+    #[test]
+    fn return_outside_word_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("return", None);
+        assert_eq!(result.unwrap_err().0, "Unable to return outside of a word");
+    }
+
+    /// Evaluate code and return everything it printed.
+    fn printed(code: &str) -> String {
+        let mut interpreter = Interpreter::new(());
+        let output = SharedOutput::new();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.evaluate(code, None).unwrap();
+        output.contents()
+    }
+
+    #[test]
+    fn print_stack_writes_to_output() {
+        assert_eq!(printed("1 \"a\" print-stack"), "[ 1 \"a\" ]\n");
+    }
+
+    #[test]
+    fn print_program_writes_to_output() {
+        assert_eq!(printed("1 print-program"), "1 print-program\n\n");
+    }
+
+    #[test]
+    fn documentation_writes_to_output() {
+        let output = printed("documentation");
+        assert!(output.starts_with("Documentation:\n"));
+        assert!(output.contains("print-stack"));
+    }
+
+    #[test]
+    fn output_can_be_read_from_another_thread() {
+        let mut interpreter = Interpreter::new(());
+        let output = SharedOutput::new();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.evaluate("\"hi\" print", None).unwrap();
+        let reader = std::thread::spawn(move || output.take());
+        assert_eq!(reader.join().unwrap(), "hi");
+    }
+
+    #[test]
+    fn print_emit_cr() {
+        assert_eq!(
+            printed("\"hp: \" print 10 print cr 72 emit 105 emit cr"),
+            "hp: 10\nHi\n"
+        );
+    }
+
+    #[test]
+    fn emit_invalid_code_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("-1 emit", None);
+        assert_eq!(result.unwrap_err().0, "Invalid character code: -1");
     }
 }
@@ -122,10 +122,17 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
                 ));
             }
 
-            if interpreter.stack.len() == 0 {
+            if interpreter.stack.is_empty() {
                 return Err(("Stack is empty".to_string(), interpreter.location()));
             }
 
+            if n == interpreter.stack.len() {
+                return Err((
+                    format!("n must be less than the stack size, got {}", n),
+                    interpreter.location(),
+                ));
+            }
+
             let last_idx = interpreter.stack.len() - 1;
             interpreter.stack.swap(last_idx - n, last_idx);
 
@@ -157,10 +164,17 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
                 ));
             }
 
-            if interpreter.stack.len() == 0 {
+            if interpreter.stack.is_empty() {
                 return Err(("Stack is empty".to_string(), interpreter.location()));
             };
 
+            if n == interpreter.stack.len() {
+                return Err((
+                    format!("n must be less than the stack size, got {}", n),
+                    interpreter.location(),
+                ));
+            }
+
             let last_idx = interpreter.stack.len() - 1;
             let item = interpreter.stack.remove(last_idx - n);
             interpreter.stack.push(item);
@@ -354,4 +368,24 @@ mod tests {
         interpreter.evaluate(code, None).unwrap();
         assert_eq!(interpreter.stack.len(), 0);
     }
+
+    #[test]
+    fn test_pluck_equal_to_stack_size_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1 2 2 pluck", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "n must be less than the stack size, got 2"
+        );
+    }
+
+    #[test]
+    fn test_rotn_equal_to_stack_size_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1 2 2 rotn", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "n must be less than the stack size, got 2"
+        );
+    }
 }
@@ -1,5 +1,20 @@
 use super::*;
 
+/// Pop a number that must be a non-negative integer, such as an index or a count.
+fn pop_count<State>(interpreter: &mut Interpreter<State>, name: &str) -> Result<usize, Err> {
+    let n = interpreter.pop_number()?;
+    if n < 0.0 || n.fract() != 0.0 || n > u32::MAX as Number {
+        return Err((
+            format!("{} must be a non-negative integer, got {}", name, n),
+            interpreter.location(),
+        ));
+    }
+    Ok(n as usize)
+}
+
+/// Largest string, in bytes, that `repeat` will build.
+const MAX_STRING_LEN: usize = 16 * 1024 * 1024;
+
 pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
     interpreter.register_builtin(
         "concat",
@@ -13,6 +28,230 @@ pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
             Ok(())
         },
     );
+
+    interpreter.register_builtin(
+        "str-len",
+        "string -- n",
+        "Pushes the number of characters in a string.",
+        "\"héllo\" str-len",
+        |interpreter| {
+            let string = interpreter.pop_string()?;
+            interpreter.push_number(string.chars().count() as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "substr",
+        "string start length -- string",
+        "Pushes the substring of length characters beginning at the start character.",
+        "\"hello\" 1 3 substr",
+        |interpreter| {
+            let length = pop_count(interpreter, "length")?;
+            let start = pop_count(interpreter, "start")?;
+            let string = interpreter.pop_string()?;
+
+            let char_count = string.chars().count();
+            if start.saturating_add(length) > char_count {
+                return Err((
+                    format!(
+                        "Index out of range, {}..{} is outside of a string with {} characters",
+                        start,
+                        start.saturating_add(length),
+                        char_count
+                    ),
+                    interpreter.location(),
+                ));
+            }
+
+            let substring = string.chars().skip(start).take(length).collect();
+            interpreter.push_string(substring);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "split",
+        "string separator -- string.. n",
+        "Splits a string on a separator. Pushes each part followed by the number of parts.",
+        "\"a,b,c\" \",\" split",
+        |interpreter| {
+            let separator = interpreter.pop_string()?;
+            let string = interpreter.pop_string()?;
+            if separator.is_empty() {
+                return Err((
+                    "Separator must not be empty".to_string(),
+                    interpreter.location(),
+                ));
+            }
+
+            let mut count = 0;
+            for part in string.split(separator.as_str()) {
+                interpreter.push_string(part.to_string());
+                count += 1;
+            }
+            interpreter.push_number(count as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "join",
+        "string.. n separator -- string",
+        "Joins n strings with a separator.",
+        "\"a\" \"b\" \"c\" 3 \", \" join",
+        |interpreter| {
+            let separator = interpreter.pop_string()?;
+            let n = pop_count(interpreter, "n")?;
+
+            let mut parts = vec![];
+            for _ in 0..n {
+                parts.push(interpreter.pop_string()?);
+            }
+            parts.reverse();
+
+            interpreter.push_string(parts.join(&separator));
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "index-of",
+        "string needle -- n",
+        "Pushes the character index of the first occurrence of needle in the string, or -1 if not found.",
+        "\"hello\" \"ll\" index-of",
+        |interpreter| {
+            let needle = interpreter.pop_string()?;
+            let string = interpreter.pop_string()?;
+            let index = match string.find(&needle) {
+                Some(byte_index) => string[..byte_index].chars().count() as Number,
+                None => -1.0,
+            };
+            interpreter.push_number(index);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "starts-with?",
+        "string prefix -- 0|1",
+        "Checks if a string starts with a prefix. Puts 1 on the stack if it does, 0 otherwise.",
+        "\"hello\" \"he\" starts-with?",
+        |interpreter| {
+            let prefix = interpreter.pop_string()?;
+            let string = interpreter.pop_string()?;
+            interpreter.push_number(string.starts_with(&prefix) as u8 as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "ends-with?",
+        "string suffix -- 0|1",
+        "Checks if a string ends with a suffix. Puts 1 on the stack if it does, 0 otherwise.",
+        "\"hello\" \"lo\" ends-with?",
+        |interpreter| {
+            let suffix = interpreter.pop_string()?;
+            let string = interpreter.pop_string()?;
+            interpreter.push_number(string.ends_with(&suffix) as u8 as Number);
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "replace",
+        "string from to -- string",
+        "Replaces all occurrences of from with to.",
+        "\"hello\" \"l\" \"L\" replace",
+        |interpreter| {
+            let to = interpreter.pop_string()?;
+            let from = interpreter.pop_string()?;
+            let string = interpreter.pop_string()?;
+            if from.is_empty() {
+                return Err((
+                    "Pattern to replace must not be empty".to_string(),
+                    interpreter.location(),
+                ));
+            }
+            interpreter.push_string(string.replace(&from, &to));
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "upper",
+        "string -- string",
+        "Converts a string to uppercase.",
+        "\"hello\" upper",
+        |interpreter| {
+            let string = interpreter.pop_string()?;
+            interpreter.push_string(string.to_uppercase());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "lower",
+        "string -- string",
+        "Converts a string to lowercase.",
+        "\"HELLO\" lower",
+        |interpreter| {
+            let string = interpreter.pop_string()?;
+            interpreter.push_string(string.to_lowercase());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "trim",
+        "string -- string",
+        "Removes leading and trailing whitespace from a string.",
+        "\"  hello  \" trim",
+        |interpreter| {
+            let string = interpreter.pop_string()?;
+            interpreter.push_string(string.trim().to_string());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "repeat",
+        "string n -- string",
+        "Repeats a string n times.",
+        "\"ab\" 3 repeat",
+        |interpreter| {
+            let n = pop_count(interpreter, "n")?;
+            let string = interpreter.pop_string()?;
+            if string.len().saturating_mul(n) > MAX_STRING_LEN {
+                return Err((
+                    format!(
+                        "Unable to repeat string {} times, the result would be longer than {} bytes",
+                        n, MAX_STRING_LEN
+                    ),
+                    interpreter.location(),
+                ));
+            }
+            interpreter.push_string(string.repeat(n));
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "chars",
+        "string -- string.. n",
+        "Pushes each character of a string followed by the number of characters.",
+        "\"abc\" chars",
+        |interpreter| {
+            let string = interpreter.pop_string()?;
+            let mut count = 0;
+            for c in string.chars() {
+                interpreter.push_string(c.to_string());
+                count += 1;
+            }
+            interpreter.push_number(count as Number);
+            Ok(())
+        },
+    );
 }
 
 #[cfg(test)]
@@ -26,4 +265,145 @@ mod tests {
         interpreter.evaluate(code, None).unwrap();
         assert_eq!(interpreter.pop_string().unwrap(), "hello world");
     }
+
+    #[test]
+    fn str_len_counts_characters() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("\"héllo\" str-len", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 5.0);
+    }
+
+    #[test]
+    fn substr_returns_substring() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("\"héllo\" 1 3 substr", None).unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "éll");
+    }
+
+    #[test]
+    fn substr_out_of_range_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("\"hello\" 3 5 substr", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Index out of range, 3..8 is outside of a string with 5 characters"
+        );
+    }
+
+    #[test]
+    fn substr_negative_start_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("\"hello\" -1 2 substr", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "start must be a non-negative integer, got -1"
+        );
+    }
+
+    #[test]
+    fn split_pushes_parts_and_count() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("\"a,b,c\" \",\" split", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 3.0);
+        assert_eq!(interpreter.pop_string().unwrap(), "c");
+        assert_eq!(interpreter.pop_string().unwrap(), "b");
+        assert_eq!(interpreter.pop_string().unwrap(), "a");
+    }
+
+    #[test]
+    fn split_empty_separator_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("\"abc\" \"\" split", None);
+        assert_eq!(result.unwrap_err().0, "Separator must not be empty");
+    }
+
+    #[test]
+    fn join_joins_strings() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("\"a,b,c\" \",\" split \" - \" join", None)
+            .unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "a - b - c");
+        assert!(interpreter.stack.is_empty());
+    }
+
+    #[test]
+    fn index_of_returns_character_index() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("\"héllo\" \"ll\" index-of \"hello\" \"z\" index-of", None)
+            .unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), -1.0);
+        assert_eq!(interpreter.pop_number().unwrap(), 2.0);
+    }
+
+    #[test]
+    fn starts_and_ends_with() {
+        let mut interpreter = Interpreter::new(());
+        let code = "\"hello\" \"he\" starts-with? \"hello\" \"he\" ends-with?";
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 0.0);
+        assert_eq!(interpreter.pop_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn replace_replaces_all() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("\"hello\" \"l\" \"L\" replace", None)
+            .unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "heLLo");
+    }
+
+    #[test]
+    fn upper_lower_trim() {
+        let mut interpreter = Interpreter::new(());
+        let code = "\"straße\" upper \"HÉLLO\" lower \"  hi \\n\" trim";
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "hi");
+        assert_eq!(interpreter.pop_string().unwrap(), "héllo");
+        assert_eq!(interpreter.pop_string().unwrap(), "STRASSE");
+    }
+
+    #[test]
+    fn repeat_repeats_string() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("\"ab\" 3 repeat", None).unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "ababab");
+    }
+
+    #[test]
+    fn repeat_too_large_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("\"x\" 1e10 repeat", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "n must be a non-negative integer, got 10000000000"
+        );
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("\"ab\" 10000000 repeat", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Unable to repeat string 10000000 times, the result would be longer than 16777216 bytes"
+        );
+    }
+
+    #[test]
+    fn repeat_fractional_count_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("\"ab\" 1.5 repeat", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "n must be a non-negative integer, got 1.5"
+        );
+    }
+
+    #[test]
+    fn chars_pushes_characters_and_count() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("\"hé\" chars", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 2.0);
+        assert_eq!(interpreter.pop_string().unwrap(), "é");
+        assert_eq!(interpreter.pop_string().unwrap(), "h");
+    }
 }
@@ -0,0 +1,141 @@
+use super::*;
+
+pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
+    interpreter.register_builtin(
+        "assert",
+        "0|1 --",
+        "Fails with an error if the value is false.",
+        "1 1 == assert",
+        |interpreter| {
+            if interpreter.pop_bool()? {
+                return Ok(());
+            }
+            Err(("Assertion failed".to_string(), interpreter.location()))
+        },
+    );
+
+    interpreter.register_builtin(
+        "assert-eq",
+        "any any --",
+        "Fails with an error if the value is not equal to the expected value on top of the stack.",
+        "1 2 + 3 assert-eq",
+        |interpreter| {
+            let expected = interpreter.pop()?;
+            let actual = interpreter.pop()?;
+            if actual == expected {
+                return Ok(());
+            }
+            Err((
+                format!(
+                    "Assertion failed: expected {}, got {}",
+                    interpreter.stringify_value(&expected),
+                    interpreter.stringify_value(&actual)
+                ),
+                interpreter.location(),
+            ))
+        },
+    );
+
+    interpreter.register_builtin(
+        "assert-stack",
+        ".. any.. n --",
+        "Fails with an error if the stack does not hold exactly the n expected values below n.",
+        "1 2 + 3 1 assert-stack",
+        |interpreter| {
+            let count = interpreter.pop_number()?;
+            if count.fract() != 0.0 || count < 0.0 {
+                return Err((
+                    format!(
+                        "Expected stack size must be a non-negative integer, got {}",
+                        count
+                    ),
+                    interpreter.location(),
+                ));
+            }
+            let count = count as usize;
+            if interpreter.stack.len() < count {
+                return Err((
+                    format!(
+                        "assert-stack expects {} values, got {}",
+                        count,
+                        interpreter.stack.len()
+                    ),
+                    interpreter.location(),
+                ));
+            }
+
+            let expected = interpreter.stack.split_off(interpreter.stack.len() - count);
+            if interpreter.stack == expected {
+                return Ok(());
+            }
+            Err((
+                format!(
+                    "Assertion failed: expected stack {}, got {}",
+                    interpreter.stringify_values(&expected),
+                    interpreter.stringify_stack()
+                ),
+                interpreter.location(),
+            ))
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_passes_on_true() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("1 assert", None).unwrap();
+        assert!(interpreter.stack.is_empty());
+    }
+
+    #[test]
+    fn assert_fails_on_false() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1 2 == assert", None);
+        assert_eq!(result, Err(("Assertion failed".into(), (1, 8).into())));
+    }
+
+    #[test]
+    fn assert_eq_compares_values() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("\"a\" \"a\" assert-eq", None).unwrap();
+        let result = interpreter.evaluate("1 2 + 4 assert-eq", None);
+        assert_eq!(result.unwrap_err().0, "Assertion failed: expected 4, got 3");
+    }
+
+    #[test]
+    fn assert_stack_compares_the_whole_stack() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("1 2 1 2 2 assert-stack", None)
+            .unwrap();
+        assert_eq!(interpreter.stack, [1.0.into(), 2.0.into()]);
+
+        let result = interpreter.evaluate("3 1 assert-stack", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Assertion failed: expected stack [ 3 ], got [ 1 2 ]"
+        );
+    }
+
+    #[test]
+    fn assert_stack_checks_count() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("0 assert-stack", None).unwrap();
+        let result = interpreter.evaluate("1 3 assert-stack", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "assert-stack expects 3 values, got 1"
+        );
+        interpreter.recover();
+        interpreter.stack.clear();
+        let result = interpreter.evaluate("1.5 assert-stack", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Expected stack size must be a non-negative integer, got 1.5"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use super::*;
+
+/// Pop a value and push 1 if it matches the predicate, 0 otherwise.
+fn push_is<State>(
+    interpreter: &mut Interpreter<State>,
+    predicate: fn(&StackValue) -> bool,
+) -> Result<(), Err> {
+    let value = interpreter.pop()?;
+    interpreter.push_number(predicate(&value) as u8 as Number);
+    Ok(())
+}
+
+pub fn register_builtins<State>(interpreter: &mut Interpreter<State>) {
+    interpreter.register_builtin(
+        "is-number?",
+        "any -- 0|1",
+        "Checks if a value is a number. Puts 1 on the stack if it is, 0 otherwise.",
+        "1 is-number?",
+        |interpreter| {
+            push_is(interpreter, |value| {
+                matches!(value, StackValue::Value(Value::Number(_)))
+            })
+        },
+    );
+
+    interpreter.register_builtin(
+        "is-string?",
+        "any -- 0|1",
+        "Checks if a value is a string. Puts 1 on the stack if it is, 0 otherwise.",
+        "\"hello\" is-string?",
+        |interpreter| {
+            push_is(interpreter, |value| {
+                matches!(value, StackValue::Value(Value::String(_)))
+            })
+        },
+    );
+
+    interpreter.register_builtin(
+        "is-address?",
+        "any -- 0|1",
+        "Checks if a value is an address. Puts 1 on the stack if it is, 0 otherwise.",
+        "var life \t life is-address?",
+        |interpreter| push_is(interpreter, |value| matches!(value, StackValue::Address(_))),
+    );
+
+    interpreter.register_builtin(
+        "is-xt?",
+        "any -- 0|1",
+        "Checks if a value is an execution token. Puts 1 on the stack if it is, 0 otherwise.",
+        "' dup is-xt?",
+        |interpreter| {
+            push_is(interpreter, |value| {
+                matches!(value, StackValue::ExecutionToken(_))
+            })
+        },
+    );
+
+    interpreter.register_builtin(
+        "is-record?",
+        "any -- 0|1",
+        "Checks if a value is a record of any type. Puts 1 on the stack if it is, 0 otherwise.",
+        "struct pos { x y } \t 1 2 make-pos is-record?",
+        |interpreter| {
+            push_is(interpreter, |value| {
+                matches!(value, StackValue::Value(Value::Record(_)))
+            })
+        },
+    );
+
+    interpreter.register_builtin(
+        "type-of",
+        "any -- string",
+        "Pushes the type name of a value: number, string, address, xt or the name of a record type.",
+        "1 type-of",
+        |interpreter| {
+            let value = interpreter.pop()?;
+            interpreter.push_string(value.type_name().to_string());
+            Ok(())
+        },
+    );
+
+    interpreter.register_builtin(
+        "assert-type",
+        "any type -- any",
+        "Returns a type error unless the value has the given type name. Leaves the value on the stack.",
+        "1 \"number\" assert-type",
+        |interpreter| {
+            let expected = interpreter.pop_string()?;
+            let value = interpreter.stack.last().ok_or((
+                "Stack is empty".to_string(),
+                interpreter.location(),
+            ))?;
+            if value.type_name() != expected {
+                return Err((
+                    format!(
+                        "Type error: expected {}, got {}",
+                        expected,
+                        value.type_name()
+                    ),
+                    interpreter.location(),
+                ));
+            }
+            Ok(())
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn predicates() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        1 is-number? "a" is-number?
+        "a" is-string? 1 is-string?
+        var life life is-address? 1 is-address?
+        ' dup is-xt? ' dup is-address?
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        let expected: Vec<StackValue> = [1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0]
+            .into_iter()
+            .map(StackValue::from)
+            .collect();
+        assert_eq!(interpreter.stack, expected);
+    }
+
+    #[test]
+    fn type_of_covers_every_type() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        struct pos { x y }
+        var life
+        1 type-of "a" type-of life type-of ' dup type-of 1 2 make-pos type-of
+        5 " " join
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(
+            interpreter.pop_string().unwrap(),
+            "number string address xt pos"
+        );
+    }
+
+    #[test]
+    fn is_record() {
+        let mut interpreter = Interpreter::new(());
+        let code = "struct pos { x y } 1 2 make-pos is-record? 1 is-record?";
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.stack, [1.0.into(), 0.0.into()]);
+    }
+
+    #[test]
+    fn assert_type_keeps_value() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("\"a\" \"string\" assert-type", None)
+            .unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "a");
+    }
+
+    #[test]
+    fn assert_type_mismatch_returns_err() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("\"a\" \"number\" assert-type", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Type error: expected number, got string"
+        );
+    }
+}
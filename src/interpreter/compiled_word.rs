@@ -0,0 +1,18 @@
+use super::*;
+
+/// A word compiled with `:`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct CompiledWord {
+    /// Instructions of the word.
+    pub instructions: Vec<Instruction>,
+    /// Locations of the instructions. Used for debugging.
+    pub locations: Vec<Location>,
+}
+
+impl CompiledWord {
+    /// Add an instruction to the word.
+    pub fn push(&mut self, instruction: Instruction, location: Location) {
+        self.instructions.push(instruction);
+        self.locations.push(location);
+    }
+}
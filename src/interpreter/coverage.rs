@@ -0,0 +1,313 @@
+use super::*;
+use std::collections::BTreeMap;
+
+/// Coverage of a single file.
+#[derive(Debug, Clone, Default)]
+struct FileCoverage {
+    /// Number of instructions run per line.
+    lines: BTreeMap<usize, u64>,
+    /// Words defined in the file, with the line of the definition and the number of calls.
+    functions: BTreeMap<String, (usize, u64)>,
+    /// Number of times the true and false branch of each `if` ran, by line and column.
+    /// None if the `if` never ran.
+    branches: BTreeMap<(usize, usize), Option<[u64; 2]>>,
+}
+
+/// Lines, words and branches run while collecting coverage, see `Interpreter::start_coverage`.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    /// Coverage per file. Code evaluated without a path is stored as `stdin`.
+    files: BTreeMap<String, FileCoverage>,
+    /// File and name of the words defined while collecting coverage.
+    words: HashMap<Address, (String, String)>,
+}
+
+impl Coverage {
+    /// Number of instructions run on a line, or None if the line has no code.
+    pub fn line_hits(&self, file: &str, line: usize) -> Option<u64> {
+        self.files.get(file)?.lines.get(&line).copied()
+    }
+
+    /// Number of times a word was called, or None if it was not defined while collecting coverage.
+    pub fn word_calls(&self, name: &str) -> Option<u64> {
+        self.files
+            .values()
+            .find_map(|file| file.functions.get(name))
+            .map(|(_, calls)| *calls)
+    }
+
+    /// Number of times the true and false branch of each `if` on a line ran, in order of their columns.
+    /// None for an `if` that never ran.
+    pub fn branch_hits(&self, file: &str, line: usize) -> Vec<Option<[u64; 2]>> {
+        let Some(file) = self.files.get(file) else {
+            return vec![];
+        };
+        file.branches
+            .range((line, 0)..=(line, usize::MAX))
+            .map(|(_, hits)| *hits)
+            .collect()
+    }
+
+    /// Returns the coverage in the lcov tracefile format.
+    pub fn to_lcov(&self) -> String {
+        let mut lcov = String::new();
+        for (name, file) in self.files.iter() {
+            lcov.push_str(&format!("TN:\nSF:{}\n", name));
+
+            for (word, (line, _)) in file.functions.iter() {
+                lcov.push_str(&format!("FN:{},{}\n", line, word));
+            }
+            for (word, (_, calls)) in file.functions.iter() {
+                lcov.push_str(&format!("FNDA:{},{}\n", calls, word));
+            }
+            let functions_hit = file.functions.values().filter(|(_, calls)| *calls > 0);
+            lcov.push_str(&format!(
+                "FNF:{}\nFNH:{}\n",
+                file.functions.len(),
+                functions_hit.count()
+            ));
+
+            let mut branches_hit = 0;
+            for (block, ((line, _), hits)) in file.branches.iter().enumerate() {
+                for branch in 0..2 {
+                    let taken = match hits {
+                        Some(hits) => {
+                            branches_hit += (hits[branch] > 0) as usize;
+                            hits[branch].to_string()
+                        }
+                        None => "-".to_string(),
+                    };
+                    lcov.push_str(&format!("BRDA:{},{},{},{}\n", line, block, branch, taken));
+                }
+            }
+            lcov.push_str(&format!(
+                "BRF:{}\nBRH:{}\n",
+                file.branches.len() * 2,
+                branches_hit
+            ));
+
+            for (line, hits) in file.lines.iter() {
+                lcov.push_str(&format!("DA:{},{}\n", line, hits));
+            }
+            let lines_hit = file.lines.values().filter(|hits| **hits > 0);
+            lcov.push_str(&format!(
+                "LF:{}\nLH:{}\nend_of_record\n",
+                file.lines.len(),
+                lines_hit.count()
+            ));
+        }
+        lcov
+    }
+
+    fn file(&mut self, location: &Location) -> &mut FileCoverage {
+        self.files.entry(file_name(location)).or_default()
+    }
+}
+
+/// Name of the file of a location in the coverage.
+fn file_name(location: &Location) -> String {
+    match &location.path {
+        Some(path) => path.display().to_string(),
+        None => "stdin".to_string(),
+    }
+}
+
+impl<State> Interpreter<State> {
+    /// Start collecting coverage, discarding the previous coverage.
+    /// Only code loaded after this is reported, including lines that never run.
+    pub fn start_coverage(&mut self) {
+        self.coverage = Some(Coverage::default());
+    }
+
+    /// Stop collecting coverage and return it.
+    pub fn stop_coverage(&mut self) -> Option<Coverage> {
+        self.coverage.take()
+    }
+
+    /// The coverage collected so far, if collecting coverage.
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    /// Add the lines and branches of newly loaded code to the coverage, starting at the instruction.
+    /// Lines of word definitions are added when the word is compiled.
+    pub(crate) fn cover_program(&mut self, start: usize) {
+        let Some(coverage) = &mut self.coverage else {
+            return;
+        };
+        let define = self.name_table.get(":").copied();
+        let if_statement = self.name_table.get("if").copied();
+
+        let mut defining = false;
+        for (instruction, location) in self.program[start..]
+            .iter()
+            .zip(&self.program_debug_locations[start..])
+        {
+            let address = instruction.get_address();
+            if !defining {
+                coverage
+                    .file(location)
+                    .lines
+                    .entry(location.line)
+                    .or_default();
+                defining = address.is_some() && address == define;
+            } else if address == Some(self.address_cache.compile_end) {
+                defining = false;
+            }
+
+            if address.is_some() && address == if_statement {
+                coverage
+                    .file(location)
+                    .branches
+                    .insert((location.line, location.column), None);
+            }
+        }
+    }
+
+    /// Add the lines of a compiled word and the word itself to the coverage.
+    pub(crate) fn cover_word(&mut self, address: Address, word: &CompiledWord) {
+        let name = self.get_name(address);
+        let location = self.location();
+        let Some(coverage) = &mut self.coverage else {
+            return;
+        };
+
+        for location in word.locations.iter() {
+            coverage
+                .file(location)
+                .lines
+                .entry(location.line)
+                .or_default();
+        }
+        coverage
+            .file(&location)
+            .functions
+            .insert(name.clone(), (location.line, 0));
+        coverage.words.insert(address, (file_name(&location), name));
+    }
+
+    /// Record that the instruction at the program counter runs.
+    pub(crate) fn cover_instruction(&mut self) {
+        let (Some(coverage), Some(location)) = (
+            &mut self.coverage,
+            self.program_debug_locations.get(self.program_counter),
+        ) else {
+            return;
+        };
+        *coverage
+            .file(location)
+            .lines
+            .entry(location.line)
+            .or_default() += 1;
+    }
+
+    /// Record a call of a compiled word.
+    pub(crate) fn cover_call(&mut self, address: Address) {
+        let Some(coverage) = &mut self.coverage else {
+            return;
+        };
+        let Some((file, name)) = coverage.words.get(&address) else {
+            return;
+        };
+        if let Some((_, calls)) = coverage
+            .files
+            .get_mut(file)
+            .and_then(|file| file.functions.get_mut(name))
+        {
+            *calls += 1;
+        }
+    }
+
+    /// Record which branch of the `if` at the program counter runs.
+    pub(crate) fn cover_branch(&mut self, value: bool) {
+        let (Some(coverage), Some(location)) = (
+            &mut self.coverage,
+            self.program_debug_locations.get(self.program_counter),
+        ) else {
+            return;
+        };
+        let hits = coverage
+            .file(location)
+            .branches
+            .entry((location.line, location.column))
+            .or_default()
+            .get_or_insert([0, 0]);
+        hits[if value { 0 } else { 1 }] += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = ": sign \"\" \"\" \"\"
+    0 < if
+        1
+    else
+        -1
+    end
+;
+: unused \"\" \"\" \"\"
+    drop
+;
+5 sign
+0 if 1 end";
+
+    fn coverage(code: &str) -> Coverage {
+        let mut interpreter = Interpreter::new(());
+        interpreter.start_coverage();
+        interpreter
+            .evaluate(code, Some("game.stackr".into()))
+            .unwrap();
+        interpreter.stop_coverage().unwrap()
+    }
+
+    #[test]
+    fn records_lines() {
+        let coverage = coverage(CODE);
+        assert_eq!(coverage.line_hits("game.stackr", 1), Some(1));
+        assert_eq!(coverage.line_hits("game.stackr", 2), Some(3));
+        assert_eq!(coverage.line_hits("game.stackr", 3), Some(1));
+        assert_eq!(coverage.line_hits("game.stackr", 5), Some(0));
+        assert_eq!(coverage.line_hits("game.stackr", 9), Some(0));
+        assert_eq!(coverage.line_hits("game.stackr", 11), Some(2));
+        assert_eq!(coverage.line_hits("game.stackr", 7), None);
+    }
+
+    #[test]
+    fn records_words_and_branches() {
+        let coverage = coverage(CODE);
+        assert_eq!(coverage.word_calls("sign"), Some(1));
+        assert_eq!(coverage.word_calls("unused"), Some(0));
+        assert_eq!(coverage.word_calls("drop"), None);
+        assert_eq!(coverage.branch_hits("game.stackr", 2), [Some([1, 0])]);
+        assert_eq!(coverage.branch_hits("game.stackr", 12), [Some([0, 1])]);
+    }
+
+    #[test]
+    fn unrun_branches_are_reported() {
+        let coverage = coverage(": f \"\" \"\" \"\" 1 if 2 end ;");
+        assert_eq!(coverage.branch_hits("game.stackr", 1), [None]);
+        assert!(coverage.to_lcov().contains("BRDA:1,0,0,-\nBRDA:1,0,1,-\n"));
+    }
+
+    #[test]
+    fn exports_lcov() {
+        let lcov = coverage(CODE).to_lcov();
+        assert!(lcov.starts_with("TN:\nSF:game.stackr\n"), "{}", lcov);
+        assert!(
+            lcov.contains("FN:1,sign\nFN:8,unused\nFNDA:1,sign\nFNDA:0,unused\nFNF:2\nFNH:1\n"),
+            "{}",
+            lcov
+        );
+        assert!(
+            lcov.contains(
+                "BRDA:2,0,0,1\nBRDA:2,0,1,0\nBRDA:12,1,0,0\nBRDA:12,1,1,1\nBRF:4\nBRH:2\n"
+            ),
+            "{}",
+            lcov
+        );
+        assert!(lcov.contains("DA:5,0\n"), "{}", lcov);
+        assert!(lcov.ends_with("LH:6\nend_of_record\n"), "{}", lcov);
+    }
+}
@@ -0,0 +1,468 @@
+use super::*;
+
+/// A place to stop execution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breakpoint {
+    /// Stop before a word is called.
+    Word(String),
+    /// Stop at the first instruction of a line. A path of None matches code evaluated without a path.
+    Line { path: Option<PathBuf>, line: usize },
+}
+
+impl Breakpoint {
+    /// Parse a breakpoint from a word name or `file:line`.
+    /// `stdin:line` sets a breakpoint in code evaluated without a path.
+    pub fn parse(breakpoint: &str) -> Self {
+        if let Some((path, line)) = breakpoint.rsplit_once(':') {
+            if let (false, Ok(line)) = (path.is_empty(), line.parse()) {
+                let path = (path != "stdin").then(|| PathBuf::from(path));
+                return Breakpoint::Line { path, line };
+            }
+        }
+        Breakpoint::Word(breakpoint.to_string())
+    }
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Breakpoint::Word(word) => write!(f, "{}", word),
+            Breakpoint::Line {
+                path: Some(path),
+                line,
+            } => write!(f, "{}:{}", path.display(), line),
+            Breakpoint::Line { path: None, line } => write!(f, "stdin:{}", line),
+        }
+    }
+}
+
+/// What to do after the debugger stopped.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DebugAction {
+    /// Run until the next breakpoint.
+    Continue,
+    /// Stop at the next instruction, including instructions of called words.
+    StepInto,
+    /// Stop at the next instruction of the current word, running called words without stopping.
+    StepOver,
+    /// Stop at the next instruction after the current word returns.
+    StepOut,
+}
+
+/// Why the debugger stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopReason {
+    /// A breakpoint was hit.
+    Breakpoint(Breakpoint),
+    /// A step finished.
+    Step,
+}
+
+/// Where the debugger stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugStop {
+    /// Why the debugger stopped.
+    pub reason: StopReason,
+    /// Location of the instruction about to run.
+    pub location: Location,
+    /// The instruction about to run.
+    pub instruction: String,
+    /// The compiled word running, None for the top level program.
+    pub word: Option<String>,
+    /// Number of compiled words on the return stack.
+    pub depth: usize,
+}
+
+/// A type alias for the hook called every time the debugger stops.
+/// Receives the interpreter, which can be inspected, and returns how to continue.
+pub type DebugHook<State> =
+    Box<dyn FnMut(&mut Interpreter<State>, &DebugStop) -> DebugAction + Send>;
+
+/// When to stop for a step.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Step {
+    /// Only stop at breakpoints.
+    None,
+    /// Stop at the next instruction.
+    Into,
+    /// Stop at the next instruction at the depth or less.
+    Over(usize),
+    /// Stop at the next instruction below the depth.
+    Out(usize),
+}
+
+/// State of the debugger.
+pub(crate) struct Debugger<State> {
+    breakpoints: Vec<Breakpoint>,
+    hook: Option<DebugHook<State>>,
+    step: Step,
+}
+
+impl<State> Default for Debugger<State> {
+    fn default() -> Self {
+        Self {
+            breakpoints: vec![],
+            hook: None,
+            step: Step::None,
+        }
+    }
+}
+
+impl<State> Interpreter<State> {
+    /// Set the hook called every time the debugger stops, returning the previous one.
+    /// The debugger only stops while a hook is set.
+    pub fn set_debug_hook(&mut self, hook: Option<DebugHook<State>>) -> Option<DebugHook<State>> {
+        std::mem::replace(&mut self.debugger.hook, hook)
+    }
+
+    /// Add a breakpoint.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.debugger.breakpoints.contains(&breakpoint) {
+            self.debugger.breakpoints.push(breakpoint);
+        }
+    }
+
+    /// Remove a breakpoint. Returns whether it existed.
+    pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) -> bool {
+        let count = self.debugger.breakpoints.len();
+        self.debugger.breakpoints.retain(|b| b != breakpoint);
+        self.debugger.breakpoints.len() != count
+    }
+
+    /// Remove all breakpoints.
+    pub fn clear_breakpoints(&mut self) {
+        self.debugger.breakpoints.clear();
+    }
+
+    /// All breakpoints, in the order they were added.
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.debugger.breakpoints
+    }
+
+    /// Stop at the next instruction that runs.
+    pub fn debug_step(&mut self) {
+        self.debugger.step = Step::Into;
+    }
+
+    /// The return stack, innermost call last.
+    /// Each entry is the calling word, None for the top level program, and the location of the call.
+    pub fn return_stack(&self) -> Vec<(Option<String>, Location)> {
+        self.frames
+            .iter()
+            .map(|frame| {
+                let word = frame.word.map(|word| self.get_name(word));
+                let location = frame
+                    .program_debug_locations
+                    .get(frame.program_counter)
+                    .cloned()
+                    .unwrap_or_default();
+                (word, location)
+            })
+            .collect()
+    }
+
+    /// All variables, values and constants with their current contents, sorted by name.
+    pub fn variables(&self) -> Vec<(String, StackValue)> {
+        let mut variables: Vec<_> = self
+            .name_table
+            .iter()
+            .filter_map(|(name, address)| {
+                let value = match self.ram.get(address)? {
+                    // Variables point to the cell holding their contents.
+                    RamValue::Address(cell) => match self.ram.get(cell)? {
+                        RamValue::Value(value) => StackValue::Value(value.clone()),
+                        RamValue::Address(address) => StackValue::Address(*address),
+                        RamValue::ExecutionToken(address) => StackValue::ExecutionToken(*address),
+                        _ => return None,
+                    },
+                    RamValue::Constant(value) | RamValue::NamedValue(value) => value.clone(),
+                    _ => return None,
+                };
+                Some((name.clone(), value))
+            })
+            .collect();
+        variables.sort_by(|a, b| a.0.cmp(&b.0));
+        variables
+    }
+
+    /// Execute the instruction at the program counter, stopping in the debugger first if needed.
+    pub(crate) fn step_instruction(&mut self, instruction: Instruction) -> Result<(), Err> {
+        if self.trace_hook.is_some() {
+            self.trace_instruction(&instruction);
+        }
+        if self.coverage.is_some() {
+            self.cover_instruction();
+        }
+        if self.debugger.hook.is_some() {
+            if let Some(reason) = self.stop_reason(&instruction) {
+                self.debug_stop(reason, &instruction);
+            }
+        }
+        self.execute_instruction(instruction)
+    }
+
+    /// Returns why the debugger should stop before running the instruction, if it should.
+    fn stop_reason(&self, instruction: &Instruction) -> Option<StopReason> {
+        let depth = self.frames.len();
+        let stepped = match self.debugger.step {
+            Step::None => false,
+            Step::Into => true,
+            Step::Over(over) => depth <= over,
+            Step::Out(out) => depth < out,
+        };
+        if stepped {
+            return Some(StopReason::Step);
+        }
+
+        let location = self.location();
+        let starts_line = self.program_counter == 0
+            || self
+                .program_debug_locations
+                .get(self.program_counter - 1)
+                .is_none_or(|previous| previous.line != location.line);
+
+        self.debugger
+            .breakpoints
+            .iter()
+            .find(|breakpoint| match breakpoint {
+                Breakpoint::Word(word) => {
+                    instruction.get_address().is_some()
+                        && instruction.get_address() == self.name_table.get(word).copied()
+                }
+                Breakpoint::Line { path, line } => {
+                    starts_line && location.line == *line && location.path == *path
+                }
+            })
+            .map(|breakpoint| StopReason::Breakpoint(breakpoint.clone()))
+    }
+
+    /// Stop in the debugger and let the hook decide how to continue.
+    fn debug_stop(&mut self, reason: StopReason, instruction: &Instruction) {
+        let Some(mut hook) = self.debugger.hook.take() else {
+            return;
+        };
+
+        let depth = self.frames.len();
+        let stop = DebugStop {
+            reason,
+            location: self.location(),
+            instruction: instruction.display_type(self),
+            word: self.current_word.map(|word| self.get_name(word)),
+            depth,
+        };
+        let action = hook(self, &stop);
+        self.debugger.step = match action {
+            DebugAction::Continue => Step::None,
+            DebugAction::StepInto => Step::Into,
+            DebugAction::StepOver => Step::Over(depth),
+            DebugAction::StepOut => Step::Out(depth),
+        };
+
+        // Keep a hook set by the hook itself.
+        if self.debugger.hook.is_none() {
+            self.debugger.hook = Some(hook);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const CODE: &str =
+        ": double \"\" \"\" \"\" 2 * ;\n: quad \"\" \"\" \"\" double double ;\n3 quad\n1 +";
+
+    /// Run the code, answering every stop with the next action. Returns the stops.
+    fn debug(
+        interpreter: &mut Interpreter<()>,
+        code: &str,
+        actions: &[DebugAction],
+    ) -> Vec<DebugStop> {
+        let stops = Arc::new(Mutex::new(vec![]));
+        let recorded = stops.clone();
+        let mut actions = std::collections::VecDeque::from(actions.to_vec());
+        interpreter.set_debug_hook(Some(Box::new(move |_, stop| {
+            recorded.lock().unwrap().push(stop.clone());
+            actions.pop_front().unwrap_or(DebugAction::Continue)
+        })));
+        interpreter.evaluate(code, None).unwrap();
+        let stops = stops.lock().unwrap().clone();
+        stops
+    }
+
+    /// Summarize stops as `word@line:column instruction`.
+    fn summary(stops: &[DebugStop]) -> Vec<String> {
+        stops
+            .iter()
+            .map(|stop| {
+                format!(
+                    "{}@{}:{} {}",
+                    stop.word.as_deref().unwrap_or("-"),
+                    stop.location.line,
+                    stop.location.column,
+                    stop.instruction
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_breakpoints() {
+        assert_eq!(Breakpoint::parse("dup"), Breakpoint::Word("dup".into()));
+        assert_eq!(Breakpoint::parse(":"), Breakpoint::Word(":".into()));
+        assert_eq!(
+            Breakpoint::parse("game.stackr:12"),
+            Breakpoint::Line {
+                path: Some("game.stackr".into()),
+                line: 12
+            }
+        );
+        assert_eq!(
+            Breakpoint::parse("stdin:3"),
+            Breakpoint::Line {
+                path: None,
+                line: 3
+            }
+        );
+        assert_eq!(Breakpoint::parse("stdin:3").to_string(), "stdin:3");
+    }
+
+    #[test]
+    fn word_breakpoint_stops_before_call() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.add_breakpoint(Breakpoint::parse("double"));
+        let stops = debug(&mut interpreter, CODE, &[]);
+        assert_eq!(summary(&stops), ["quad@2:17 double", "quad@2:24 double"]);
+        assert_eq!(
+            stops[0].reason,
+            StopReason::Breakpoint(Breakpoint::Word("double".into()))
+        );
+        assert_eq!(stops[0].depth, 1);
+    }
+
+    #[test]
+    fn line_breakpoint_stops_once_per_line() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.add_breakpoint(Breakpoint::parse("stdin:4"));
+        let stops = debug(&mut interpreter, CODE, &[]);
+        assert_eq!(summary(&stops), ["-@4:1 N1"]);
+    }
+
+    #[test]
+    fn step_into_over_and_out() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.add_breakpoint(Breakpoint::parse("quad"));
+        let stops = debug(
+            &mut interpreter,
+            CODE,
+            &[
+                DebugAction::StepInto,
+                DebugAction::StepOver,
+                DebugAction::StepInto,
+                DebugAction::StepOut,
+                DebugAction::StepOver,
+            ],
+        );
+        assert_eq!(
+            summary(&stops),
+            [
+                "-@3:3 quad",
+                "quad@2:17 double",
+                "quad@2:24 double",
+                "double@1:19 N2",
+                "-@4:1 N1",
+                "-@4:3 +",
+            ]
+        );
+        assert_eq!(interpreter.pop_number().unwrap(), 13.0);
+    }
+
+    #[test]
+    fn step_over_on_last_instruction_stops_in_caller() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.add_breakpoint(Breakpoint::parse("*"));
+        let stops = debug(
+            &mut interpreter,
+            CODE,
+            &[DebugAction::StepOver, DebugAction::Continue],
+        );
+        assert_eq!(summary(&stops)[..2], ["double@1:21 *", "quad@2:24 double"]);
+    }
+
+    #[test]
+    fn breakpoints_in_if_bodies() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.add_breakpoint(Breakpoint::parse("dup"));
+        let stops = debug(&mut interpreter, "1 if 2 dup end", &[]);
+        assert_eq!(summary(&stops), ["-@1:8 dup"]);
+    }
+
+    #[test]
+    fn inspect_at_stop() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.add_breakpoint(Breakpoint::parse("*"));
+        let inspected = Arc::new(Mutex::new(vec![]));
+        let recorded = inspected.clone();
+        interpreter.set_debug_hook(Some(Box::new(move |interpreter, _| {
+            let return_stack: Vec<_> = interpreter
+                .return_stack()
+                .into_iter()
+                .map(|(word, location)| format!("{} {}", word.as_deref().unwrap_or("-"), location))
+                .collect();
+            recorded.lock().unwrap().push((
+                interpreter.stack().to_vec(),
+                return_stack,
+                interpreter.variables(),
+            ));
+            DebugAction::Continue
+        })));
+        let code = "var hp 10 hp set 5 value speed\n: double \"\" \"\" \"\" 2 * ;\n: quad \"\" \"\" \"\" double double ;\n3 quad";
+        interpreter.evaluate(code, None).unwrap();
+
+        let inspected = inspected.lock().unwrap();
+        let (stack, return_stack, variables) = &inspected[0];
+        assert_eq!(stack, &[3.0.into(), 2.0.into()]);
+        assert_eq!(return_stack, &["- stdin:4:3", "quad stdin:3:17"]);
+        assert_eq!(
+            variables,
+            &[
+                ("hp".to_string(), 10.0.into()),
+                ("speed".to_string(), 5.0.into())
+            ]
+        );
+    }
+
+    #[test]
+    fn no_stops_without_hook() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.add_breakpoint(Breakpoint::parse("double"));
+        interpreter.evaluate(CODE, None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 13.0);
+    }
+
+    #[test]
+    fn remove_breakpoint() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.add_breakpoint(Breakpoint::parse("double"));
+        interpreter.add_breakpoint(Breakpoint::parse("double"));
+        assert_eq!(interpreter.breakpoints().len(), 1);
+        assert!(interpreter.remove_breakpoint(&Breakpoint::parse("double")));
+        assert!(!interpreter.remove_breakpoint(&Breakpoint::parse("double")));
+        let stops = debug(&mut interpreter, CODE, &[]);
+        assert!(stops.is_empty());
+    }
+
+    #[test]
+    fn interpreter_with_hooks_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Interpreter<()>>();
+
+        let mut interpreter = Interpreter::new(());
+        interpreter.add_breakpoint(Breakpoint::parse("double"));
+        let stops = std::thread::spawn(move || debug(&mut interpreter, CODE, &[]))
+            .join()
+            .unwrap();
+        assert_eq!(stops.len(), 2);
+    }
+}
@@ -0,0 +1,85 @@
+use super::*;
+
+/// Documentation of a word, registered with the word or given in its `:` definition.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WordDocumentation {
+    /// The stack effect, e.g. `n -- n`. Empty if not given.
+    pub stack_modification: String,
+    /// What the word does.
+    pub documentation: String,
+    /// Example usage. Empty if not given.
+    pub example: String,
+}
+
+impl std::fmt::Display for WordDocumentation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.stack_modification.is_empty() {
+            write!(f, "\t( {} )", self.stack_modification)?;
+        }
+        write!(f, "\n\t{}", self.documentation)?;
+        if !self.example.is_empty() {
+            write!(f, "\n\tExample '{}'", self.example)?;
+        }
+        Ok(())
+    }
+}
+
+impl<State> Interpreter<State> {
+    /// Returns the documentation of a word, if it has any.
+    pub fn word_documentation(&self, name: &str) -> Option<&WordDocumentation> {
+        self.documentation_table.get(self.name_table.get(name)?)
+    }
+
+    /// Returns the names of all defined words, sorted.
+    pub fn word_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .name_table
+            .iter()
+            .filter(|(_, address)| self.ram.contains_key(address))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documentation_of_words() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate(
+                ": squared \"squares a number\" \"n -- n\" \"2 squared\" dup * ;",
+                None,
+            )
+            .unwrap();
+        let documentation = interpreter.word_documentation("squared").unwrap();
+        assert_eq!(documentation.stack_modification, "n -- n");
+        assert_eq!(documentation.documentation, "squares a number");
+        assert_eq!(
+            documentation.to_string(),
+            "\t( n -- n )\n\tsquares a number\n\tExample '2 squared'"
+        );
+        assert_eq!(
+            interpreter
+                .word_documentation("dup")
+                .unwrap()
+                .stack_modification,
+            "n -- n n"
+        );
+        assert!(interpreter.word_documentation("missing").is_none());
+    }
+
+    #[test]
+    fn names_of_defined_words() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("var score", None).unwrap();
+        let names = interpreter.word_names();
+        assert!(names.contains(&"dup"));
+        assert!(names.contains(&"score"));
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
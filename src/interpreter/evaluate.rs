@@ -11,108 +11,59 @@ impl<State> Interpreter<State> {
 
     /// Load a program into the interpreter.
     pub(crate) fn load_program(&mut self, code: &str, path: Option<PathBuf>) -> Result<(), Err> {
-        let mut location = Location::new(path);
-
-        let mut maybe_make_word = |buffer: &mut String, word_location: &Location| {
-            if !buffer.is_empty() {
-                let word = buffer.trim();
-                let location = word_location.clone();
-                self.program_debug_locations.push(location);
-
-                // Try to parse number
-                let instruction = if let Ok(number) = word.parse::<Number>() {
-                    Instruction::PushNumber(number)
-                } else if word.starts_with('"') {
-                    // Remove the first and last character
-                    let mut word = word.to_string();
-                    word.remove(0);
-                    word.pop();
-                    Instruction::PushString(word)
-                } else {
-                    Instruction::Address(self.get_address(word))
-                };
-                self.program.push(instruction);
-                *buffer = String::new();
-            }
-        };
-
-        let mut buffer = String::new();
-        let mut word_location = location.clone();
-        let mut making_string = false;
-        let mut made_string = false;
-
-        for c in code.chars() {
-            if c == '"' {
-                if !making_string {
-                    making_string = true;
-                } else {
-                    let last_char = buffer.chars().last();
-                    if last_char != Some('\\') {
-                        making_string = false;
-                        made_string = true;
-                        buffer.push(c);
-                    }
-                }
-            }
-            if !making_string && c.is_whitespace() || made_string {
-                made_string = false;
-                maybe_make_word(&mut buffer, &word_location);
-            } else {
-                // set word location to current location if buffer is empty
-                // as this is the first character of the word
-                if buffer.is_empty() {
-                    word_location = location.clone();
-                }
-                buffer.push(c);
-            }
-
-            // Update location
-            if c == '\n' {
-                location.new_line();
-            } else {
-                location.next();
-            }
-        }
-
-        if making_string {
-            return Err(("Unclosed string".into(), word_location));
+        for token in tokenize(code, path)? {
+            let instruction = match token.kind {
+                TokenKind::Number(number) => Instruction::PushNumber(number),
+                TokenKind::String(string) => Instruction::PushString(string),
+                TokenKind::Word(word) => Instruction::Address(self.get_address(&word)),
+            };
+            self.program.push(instruction);
+            self.program_debug_locations.push(token.location);
         }
 
-        // Handle case if buffer is not empty
-        maybe_make_word(&mut buffer, &word_location);
-
         Ok(())
     }
 
     /// Execute the program.
     pub(crate) fn execute(&mut self) -> Result<(), Err> {
-        while self.program_counter < self.program.len() {
+        while self.program_counter < self.program.len() && self.exit_code.is_none() {
             let instruction = self.program[self.program_counter].clone();
-            self.execute_instruction(instruction)?;
+            self.step_instruction(instruction)?;
             self.program_counter += 1;
         }
 
+        // Skip the rest of the program when exiting.
+        if self.exit_code.is_some() && self.frames.is_empty() {
+            self.program_counter = self.program.len();
+        }
+
         Ok(())
     }
 
+    /// Returns whether the running program or word was stopped early by `exit` or `return`.
+    pub(crate) fn is_unwinding(&self) -> bool {
+        self.program_counter >= self.program.len() || self.exit_code.is_some()
+    }
+
     /// Execute an instruction.
     pub(crate) fn execute_instruction(&mut self, instruction: Instruction) -> Result<(), Err> {
+        if self.profile.is_some() {
+            return self.execute_profiled(instruction);
+        }
+        self.dispatch_instruction(instruction)
+    }
+
+    /// Execute an instruction without profiling it.
+    pub(crate) fn dispatch_instruction(&mut self, instruction: Instruction) -> Result<(), Err> {
         match instruction {
             Instruction::PushString(string) => self.push_string(string),
             Instruction::PushNumber(number) => self.push_number(number),
             Instruction::Address(address) => {
                 // If we are in read mode, we only want to push the address if it is not the end of the read mode
                 if self.read_mode != ReadMode::Off && address != self.address_cache.read_mode_end {
-                    if self.read_mode == ReadMode::SingleWord {
+                    if let ReadMode::SingleWord(action) = self.read_mode {
                         self.read_mode = ReadMode::Off;
-
-                        // Only insert a new address if it is not already in the ram
-                        if !self.ram.contains_key(&address) {
-                            let variable_location = self.next_address;
-                            self.next_address = self.next_address.next();
-                            self.ram
-                                .insert(address, RamValue::Address(variable_location));
-                        }
+                        self.read_single_word(action, address)?;
                     } else {
                         self.push_address(address);
                     }
@@ -122,8 +73,8 @@ impl<State> Interpreter<State> {
                         format!("Address not found: {}", self.get_name(address)),
                         self.location(),
                     ))?;
-                    let ops_to_execute = match contents {
-                        RamValue::Compiled(ops) => Some(ops.clone()),
+                    let word_to_call = match contents {
+                        RamValue::Compiled(word) => Some(word.clone()),
                         RamValue::BuiltIn(method) => {
                             method(self)?;
                             None
@@ -136,15 +87,101 @@ impl<State> Interpreter<State> {
                             self.stack.push(address.into());
                             None
                         }
-                    };
-                    if let Some(ops) = ops_to_execute {
-                        for op in ops {
-                            self.execute_instruction(op)?;
+                        RamValue::ExecutionToken(address) => {
+                            self.stack.push(StackValue::ExecutionToken(*address));
+                            None
+                        }
+                        RamValue::Constant(value) | RamValue::NamedValue(value) => {
+                            self.stack.push(value.clone());
+                            None
                         }
+                        RamValue::Deferred(Some(_)) => {
+                            let target = self.resolve_deferred(address)?;
+                            return self.execute_instruction(Instruction::Address(target));
+                        }
+                        RamValue::Record(word) => {
+                            let word = word.clone();
+                            return self.run_record_word(word);
+                        }
+                        RamValue::Deferred(None) => {
+                            return Err((
+                                format!("Deferred word not set: {}", self.get_name(address)),
+                                self.location(),
+                            ));
+                        }
+                    };
+                    if let Some(word) = word_to_call {
+                        self.call_compiled(address, word)?;
                     }
                 }
             }
         }
         Ok(())
     }
+
+    /// Follow a chain of deferred words to the word it ends at.
+    /// Returns an error if the chain loops back on itself.
+    fn resolve_deferred(&self, address: Address) -> Result<Address, Err> {
+        let mut visited = HashSet::from([address]);
+        let mut target = address;
+        while let Some(RamValue::Deferred(Some(next))) = self.ram.get(&target) {
+            if !visited.insert(*next) {
+                return Err((
+                    format!("Deferred cycle: {} calls itself", self.get_name(address)),
+                    self.location(),
+                ));
+            }
+            target = *next;
+        }
+        Ok(target)
+    }
+
+    /// Run a compiled word.
+    /// The state of the caller is saved on the return stack and restored afterwards.
+    pub(crate) fn call_compiled(
+        &mut self,
+        address: Address,
+        word: CompiledWord,
+    ) -> Result<(), Err> {
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err((
+                format!(
+                    "Call stack overflow in {}, calls nested more than {} deep",
+                    self.get_name(address),
+                    MAX_CALL_DEPTH
+                ),
+                self.location(),
+            ));
+        }
+        if self.coverage.is_some() {
+            self.cover_call(address);
+        }
+
+        let frame = Frame {
+            word: self.current_word.replace(address),
+            program: std::mem::replace(&mut self.program, word.instructions),
+            program_debug_locations: std::mem::replace(
+                &mut self.program_debug_locations,
+                word.locations,
+            ),
+            program_counter: std::mem::take(&mut self.program_counter),
+            program_counter_stack: std::mem::take(&mut self.program_counter_stack),
+            break_loop: std::mem::take(&mut self.break_loop),
+        };
+        self.frames.push(frame);
+
+        let result = self.execute();
+
+        // Restore the caller, even on errors, so the program can continue.
+        if let Some(frame) = self.frames.pop() {
+            self.current_word = frame.word;
+            self.program = frame.program;
+            self.program_debug_locations = frame.program_debug_locations;
+            self.program_counter = frame.program_counter;
+            self.program_counter_stack = frame.program_counter_stack;
+            self.break_loop = frame.break_loop;
+        }
+
+        result
+    }
 }
@@ -0,0 +1,19 @@
+use super::*;
+
+/// The state of a caller, saved while a compiled word runs.
+/// The stack of frames makes up the return stack.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Frame {
+    /// Word that was running, None for the top level program.
+    pub word: Option<Address>,
+    /// Program that was running.
+    pub program: Vec<Instruction>,
+    /// Locations of the program instructions.
+    pub program_debug_locations: Vec<Location>,
+    /// Program counter of the caller, pointing at the call.
+    pub program_counter: usize,
+    /// Program counter stack used for loops.
+    pub program_counter_stack: Vec<usize>,
+    /// Whether the caller was breaking out of a loop.
+    pub break_loop: bool,
+}
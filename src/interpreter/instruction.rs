@@ -16,8 +16,8 @@ impl Instruction {
     pub fn display_type<State>(&self, interpreter: &Interpreter<State>) -> String {
         match self {
             Instruction::PushNumber(n) => format!("N{}", n),
-            Instruction::PushString(s) => format!("\"{}\"", s),
-            Instruction::Address(a) => format!("{}", interpreter.get_name(*a)),
+            Instruction::PushString(s) => escape_string(s),
+            Instruction::Address(a) => interpreter.get_name(*a),
         }
     }
 
@@ -0,0 +1,421 @@
+use super::*;
+
+/// The kind of a token read from source code.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TokenKind {
+    /// A number literal.
+    Number(Number),
+    /// A string literal with all escapes decoded.
+    String(String),
+    /// A word.
+    Word(String),
+}
+
+/// A token read from source code.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    /// The kind of token.
+    pub kind: TokenKind,
+    /// Location of the first character of the token.
+    pub location: Location,
+}
+
+/// Tokenizes source code.
+struct Lexer {
+    /// Characters of the source code.
+    chars: Vec<char>,
+    /// Index of the next character.
+    idx: usize,
+    /// Location of the next character.
+    location: Location,
+}
+
+impl Lexer {
+    /// Create a new lexer.
+    fn new(code: &str, path: Option<PathBuf>) -> Self {
+        Self {
+            chars: code.chars().collect(),
+            idx: 0,
+            location: Location::new(path),
+        }
+    }
+
+    /// Peek at the character `offset` characters ahead.
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.idx + offset).copied()
+    }
+
+    /// Consume the next character, updating the location.
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.idx += 1;
+
+        if c == '\n' {
+            self.location.new_line();
+        } else {
+            self.location.next();
+        }
+
+        Some(c)
+    }
+
+    /// Tokenize the entire source code.
+    fn tokenize(mut self) -> Result<Vec<Token>, Err> {
+        let mut tokens = vec![];
+
+        while let Some(c) = self.peek(0) {
+            if c.is_whitespace() {
+                self.bump();
+                continue;
+            }
+
+            let location = self.location.clone();
+            let kind = if c == '"' {
+                self.bump();
+                TokenKind::String(self.read_string(&location)?)
+            } else {
+                let word = self.read_word();
+
+                // A word such as `r` or `r##` directly followed by a quote starts a raw string.
+                let is_raw_prefix = word.starts_with('r') && word[1..].chars().all(|c| c == '#');
+                if is_raw_prefix && self.peek(0) == Some('"') {
+                    self.bump();
+                    TokenKind::String(self.read_raw_string(word.len() - 1, &location)?)
+                } else if let Some(number) = parse_literal(&word) {
+                    TokenKind::Number(number.map_err(|e| (e, location.clone()))?)
+                } else {
+                    TokenKind::Word(word)
+                }
+            };
+
+            tokens.push(Token { kind, location });
+        }
+
+        Ok(tokens)
+    }
+
+    /// Read a word until whitespace or the start of a string.
+    fn read_word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek(0) {
+            if c.is_whitespace() || c == '"' {
+                break;
+            }
+            word.push(c);
+            self.bump();
+        }
+        word
+    }
+
+    /// Read a string after the opening quote, decoding escape sequences.
+    fn read_string(&mut self, start: &Location) -> Result<String, Err> {
+        let mut string = String::new();
+        loop {
+            let escape_location = self.location.clone();
+            match self.bump() {
+                None => return Err(("Unclosed string".into(), start.clone())),
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.read_escape(start, escape_location)?),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    /// Read an escape sequence after the backslash.
+    fn read_escape(&mut self, start: &Location, location: Location) -> Result<char, Err> {
+        let c = match self.bump() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('u') => return self.read_unicode_escape(location),
+            Some(c) => return Err((format!("Unknown escape sequence '\\{}'", c), location)),
+            None => return Err(("Unclosed string".into(), start.clone())),
+        };
+        Ok(c)
+    }
+
+    /// Read a unicode escape of the form `{XXXX}` after the `\u`.
+    fn read_unicode_escape(&mut self, location: Location) -> Result<char, Err> {
+        let err = || ("Invalid unicode escape".to_string(), location.clone());
+
+        if self.bump() != Some('{') {
+            return Err(err());
+        }
+
+        let mut hex = String::new();
+        loop {
+            match self.bump() {
+                Some('}') => break,
+                Some(c) if c.is_ascii_hexdigit() && hex.len() < 6 => hex.push(c),
+                _ => return Err(err()),
+            }
+        }
+
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(err)
+    }
+
+    /// Read a raw string after the opening quote.
+    /// The string ends at a quote followed by `hashes` number of '#'.
+    fn read_raw_string(&mut self, hashes: usize, start: &Location) -> Result<String, Err> {
+        let mut string = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(("Unclosed string".into(), start.clone())),
+                Some('"') if (0..hashes).all(|i| self.peek(i) == Some('#')) => {
+                    for _ in 0..hashes {
+                        self.bump();
+                    }
+                    return Ok(string);
+                }
+                Some(c) => string.push(c),
+            }
+        }
+    }
+}
+
+/// Tokenize source code.
+pub(crate) fn tokenize(code: &str, path: Option<PathBuf>) -> Result<Vec<Token>, Err> {
+    Lexer::new(code, path).tokenize()
+}
+
+/// Parse a number literal.
+/// Supports decimal, hex (`0xFF`), binary (`0b1010`) and octal (`0o17`) literals.
+/// Underscores may be used to separate digits, e.g. `1_000_000`.
+/// Integer literals that a number can't represent exactly, e.g. `0x1000001`, are rejected.
+pub(crate) fn parse_number(word: &str) -> Option<Number> {
+    parse_literal(word)?.ok()
+}
+
+/// Parse a number literal. Returns None if the word isn't one,
+/// and an error if it is an integer that a number can't represent exactly.
+fn parse_literal(word: &str) -> Option<Result<Number, String>> {
+    let (negative, unsigned) = match word.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, word),
+    };
+
+    let radix = match unsigned.get(..2) {
+        Some("0x") | Some("0X") => Some(16),
+        Some("0b") | Some("0B") => Some(2),
+        Some("0o") | Some("0O") => Some(8),
+        _ => None,
+    };
+
+    let digits = match radix {
+        Some(_) => strip_digit_separators(&unsigned[2..])?,
+        // Separators are only allowed between digits of a decimal number.
+        None if word.contains('_') && !unsigned.starts_with(|c: char| c.is_ascii_digit()) => {
+            return None
+        }
+        None if word.contains('_') => strip_digit_separators(unsigned)?,
+        None => unsigned.to_string(),
+    };
+    if radix.is_none() && !digits.bytes().all(|b| b.is_ascii_digit()) {
+        let literal = if negative {
+            format!("-{}", digits)
+        } else {
+            digits
+        };
+        return literal.parse::<Number>().ok().map(Ok);
+    }
+
+    let radix = radix.unwrap_or(10);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    // Integers are rejected rather than rounded to the nearest number.
+    let number = u128::from_str_radix(&digits, radix)
+        .ok()
+        .map(|value| (value, value as Number))
+        .filter(|(value, number)| *number as u128 == *value)
+        .map(|(_, number)| number);
+    Some(match number {
+        Some(number) if negative => Ok(-number),
+        Some(number) => Ok(number),
+        None => Err(format!(
+            "Integer literal {} can't be represented exactly",
+            word
+        )),
+    })
+}
+
+/// Remove `_` digit separators. Returns None if the digits are empty or end in a separator.
+fn strip_digit_separators(digits: &str) -> Option<String> {
+    if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_') {
+        return None;
+    }
+    Some(digits.replace('_', ""))
+}
+
+/// Returns the string as a quoted string literal, escaping characters as needed.
+/// Tokenizing the result gives back the original string.
+pub(crate) fn escape_string(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len() + 2);
+    escaped.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\0' => escaped.push_str("\\0"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(code: &str) -> Vec<TokenKind> {
+        tokenize(code, None)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn columns_start_at_one_on_every_line() {
+        let locations: Vec<(usize, usize)> = tokenize("a bc\nd  e", None)
+            .unwrap()
+            .into_iter()
+            .map(|token| (token.location.line, token.location.column))
+            .collect();
+        assert_eq!(locations, [(1, 1), (1, 3), (2, 1), (2, 4)]);
+    }
+
+    #[test]
+    fn tokenizes_words_numbers_and_strings() {
+        assert_eq!(
+            kinds("1 dup \"hello world\" +"),
+            vec![
+                TokenKind::Number(1.0),
+                TokenKind::Word("dup".into()),
+                TokenKind::String("hello world".into()),
+                TokenKind::Word("+".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_number_literals() {
+        assert_eq!(parse_number("0xFF"), Some(255.0));
+        assert_eq!(parse_number("-0x10"), Some(-16.0));
+        assert_eq!(parse_number("0b1010"), Some(10.0));
+        assert_eq!(parse_number("0o17"), Some(15.0));
+        assert_eq!(parse_number("1_000_000"), Some(1_000_000.0));
+        assert_eq!(parse_number("0b1111_0000"), Some(240.0));
+        assert_eq!(parse_number("1.5"), Some(1.5));
+        assert_eq!(parse_number("16777216"), Some(16777216.0));
+        assert_eq!(parse_number("0x80000000"), Some(2147483648.0));
+        assert_eq!(parse_number("-0x1000000"), Some(-16777216.0));
+    }
+
+    #[test]
+    fn inexact_integer_literals_return_err() {
+        for word in [
+            "0x1000001",
+            "16777217",
+            "0x7FFFFFFF",
+            "-0b1000000000000000000000001",
+        ] {
+            assert_eq!(parse_number(word), None, "{}", word);
+            assert_eq!(
+                tokenize(word, None).unwrap_err().0,
+                format!("Integer literal {} can't be represented exactly", word)
+            );
+        }
+        assert_eq!(parse_number("1e10"), Some(1e10));
+        assert_eq!(kinds("0xG"), vec![TokenKind::Word("0xG".into())]);
+    }
+
+    #[test]
+    fn invalid_number_literals_are_words() {
+        for word in ["0x", "0xG", "0b102", "_1", "1_", "-_1", "0x_1", "_"] {
+            assert_eq!(parse_number(word), None, "{}", word);
+        }
+    }
+
+    #[test]
+    fn string_keeps_trailing_spaces() {
+        assert_eq!(
+            kinds("\"hello \" \" world\""),
+            vec![
+                TokenKind::String("hello ".into()),
+                TokenKind::String(" world".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn string_ends_word() {
+        assert_eq!(
+            kinds("a\"b\"c"),
+            vec![
+                TokenKind::Word("a".into()),
+                TokenKind::String("b".into()),
+                TokenKind::Word("c".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_escapes() {
+        assert_eq!(
+            kinds(r#""a\nb\tc\\d\"e\u{1F600}""#),
+            vec![TokenKind::String("a\nb\tc\\d\"e\u{1F600}".into())]
+        );
+    }
+
+    #[test]
+    fn unknown_escape_returns_err() {
+        let err = tokenize(r#""a\q""#, None).unwrap_err();
+        assert_eq!(err, ("Unknown escape sequence '\\q'".into(), (1, 3).into()));
+    }
+
+    #[test]
+    fn invalid_unicode_escape_returns_err() {
+        let err = tokenize(r#""\u{110000}""#, None).unwrap_err();
+        assert_eq!(err.0, "Invalid unicode escape");
+
+        let err = tokenize(r#""\u1234""#, None).unwrap_err();
+        assert_eq!(err.0, "Invalid unicode escape");
+    }
+
+    #[test]
+    fn raw_strings_are_not_escaped() {
+        assert_eq!(
+            kinds("r\"a\\nb\" r#\"say \"hi\"\nthere\"#"),
+            vec![
+                TokenKind::String("a\\nb".into()),
+                TokenKind::String("say \"hi\"\nthere".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_raw_string_returns_err() {
+        let err = tokenize("r#\"hello\"", None).unwrap_err();
+        assert_eq!(err, ("Unclosed string".into(), (1, 1).into()));
+    }
+
+    #[test]
+    fn escape_string_round_trips() {
+        let input = "tab\t newline\n quote\" backslash\\ bell\u{7} emoji\u{1F600}";
+        let escaped = escape_string(input);
+        assert_eq!(kinds(&escaped), vec![TokenKind::String(input.into())]);
+    }
+}
@@ -5,7 +5,7 @@ use std::{fmt::Display, path::PathBuf};
 pub struct Location {
     /// Line number.
     pub line: usize,
-    /// Column number.
+    /// Column number, counted in characters from 1.
     pub column: usize,
     /// File name.
     pub path: Option<PathBuf>,
@@ -24,7 +24,7 @@ impl Location {
     }
 
     pub fn new_line(&mut self) {
-        self.column = 0;
+        self.column = 1;
         self.line += 1;
     }
 }
@@ -41,7 +41,7 @@ impl Display for Location {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.path {
             Some(path) => write!(f, "{}:{}:{}", path.display(), self.line, self.column),
-            None => write!(f, "{}:{}:{}", "stdin", self.line, self.column),
+            None => write!(f, "stdin:{}:{}", self.line, self.column),
         }
     }
 }
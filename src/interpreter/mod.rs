@@ -2,42 +2,102 @@
 mod address;
 /// Addresses used to identify words in the interpreter.
 mod address_cache;
+/// Symbols and words of source code, for editor tooling.
+mod analysis;
 /// Built-in words registered at runtime.
 mod built_ins;
+/// Words compiled with `:`.
+mod compiled_word;
+/// Coverage of lines, words and branches.
+mod coverage;
+/// Breakpoints and stepping through programs.
+mod debugger;
+/// Documentation of words.
+mod documentation;
 /// Contains logic for loading and executing a program.
 mod evaluate;
+/// Saved state of callers while compiled words run.
+mod frame;
 /// Instructions that can be executed by the interpreter.
 mod instruction;
+/// Tokenizes source code.
+mod lexer;
 /// Locations of the program instructions. Used for debugging.
 mod location;
+/// Output sink for the printing words.
+mod output;
+/// Call counts and timing of words.
+mod profiler;
 /// Values stored in the RAM.
 mod ram_value;
+/// Record types declared with `struct`.
+mod record;
+/// Policy for redefining words.
+mod redefinition;
+/// Read-eval-print loop.
+mod repl;
+/// REPL sessions over local sockets.
+mod repl_server;
+/// Checks programs for undefined words.
+mod resolve;
+/// Seeded random number generator.
+mod rng;
 /// Values stored on the stack.
 mod stack_value;
 /// Stringifies the program.
 mod stringify;
+/// Runs tests defined with `test:`.
+mod test_runner;
+/// Tracing of executed instructions.
+mod trace;
 
 pub(crate) use address_cache::*;
+pub(crate) use compiled_word::*;
+pub(crate) use frame::*;
 pub(crate) use instruction::*;
+pub(crate) use lexer::*;
 pub(crate) use ram_value::*;
+pub(crate) use rng::*;
 pub use stack_value::*;
 
 pub use address::*;
+pub use analysis::*;
 pub use built_ins::*;
+pub use coverage::*;
+pub use debugger::*;
+pub use documentation::*;
 pub use location::*;
-
-use std::{collections::HashMap, io::Write, path::PathBuf};
+pub use output::*;
+pub use profiler::*;
+pub use record::*;
+pub use redefinition::*;
+pub use repl::*;
+pub use repl_server::*;
+pub use test_runner::*;
+pub use trace::*;
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    path::PathBuf,
+    sync::Mutex,
+};
 
 /// A type alias for an error.
 pub type Err = (String, Location);
 /// A type alias for a number.
 pub type Number = f32;
 
+/// Maximum depth of nested calls to compiled words.
+const MAX_CALL_DEPTH: usize = 256;
+
 /// A value that is stored in RAM or on the stack.
 #[derive(Debug, PartialEq, Clone)]
 pub enum Value {
     Number(Number),
     String(String),
+    /// A record of a type declared with `struct`.
+    Record(Record),
 }
 
 /// The mode of the interpreter.
@@ -46,7 +106,26 @@ pub enum Value {
 pub(crate) enum ReadMode {
     On,
     Off,
-    SingleWord,
+    SingleWord(SingleWord),
+}
+
+/// What to do with the word read in single word read mode.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum SingleWord {
+    /// Declare a variable.
+    Var,
+    /// Define a constant from the top of the stack.
+    Constant,
+    /// Define a value from the top of the stack.
+    Value,
+    /// Update a value with the top of the stack.
+    To,
+    /// Push an execution token for the word.
+    Tick,
+    /// Declare a deferred word.
+    Defer,
+    /// Set the word a deferred word calls to the execution token on the stack.
+    Is,
 }
 
 /// The core interpreter object.
@@ -64,14 +143,24 @@ pub struct Interpreter<State> {
     program_counter: usize,
     /// Program to execute.
     program: Vec<Instruction>,
+    /// Compiled word currently running, None for the top level program.
+    current_word: Option<Address>,
+    /// Return stack. Saved state of the callers of the running compiled words.
+    frames: Vec<Frame>,
     /// Program counter stack. Used for loops/control flow.
     program_counter_stack: Vec<usize>,
     /// Whether the interpreter is in compile mode.
     compiling: bool,
+    /// Word being compiled by `:`. Immediate words may add instructions to it.
+    compile_buffer: CompiledWord,
+    /// Words marked as immediate. These run while compiling instead of being compiled.
+    immediate_words: HashSet<Address>,
+    /// The word most recently defined with `:`.
+    last_defined: Option<Address>,
     /// Locations of the program instructions. Used for debugging.
     program_debug_locations: Vec<Location>,
-    /// Whether the interpreter should quit.
-    exit: bool,
+    /// Status code set by `exit`. Evaluation stops once it is set.
+    exit_code: Option<i32>,
     /// Read mode. Determines if words should be read as addresses or evaluated.
     read_mode: ReadMode,
     /// Whether the interpreter should run a REPL.
@@ -83,9 +172,31 @@ pub struct Interpreter<State> {
     /// Stack.
     stack: Vec<StackValue>,
     /// Documentation table. Maps addresses to documentation.
-    documentation_table: HashMap<Address, String>,
+    documentation_table: HashMap<Address, WordDocumentation>,
     /// Name table. Maps names to addresses.
     name_table: HashMap<String, Address>,
+    /// Random number generator used by the random words.
+    rng: Rng,
+    /// What happens when an existing word is redefined.
+    redefinition_policy: RedefinitionPolicy,
+    /// Callback notified every time a word is redefined.
+    redefinition_callback: Option<RedefinitionCallback<State>>,
+    /// Words that can never be redefined.
+    sealed_words: HashSet<Address>,
+    /// Names of old definitions of redefined words.
+    retired_names: HashMap<Address, String>,
+    /// Where the printing words write to.
+    output: Mutex<Box<dyn Write + Send>>,
+    /// Breakpoints and the debug hook.
+    debugger: Debugger<State>,
+    /// Hook called before every instruction runs.
+    trace_hook: Option<TraceHook<State>>,
+    /// Profile collected while profiling.
+    profile: Option<Profile>,
+    /// Coverage collected while collecting coverage.
+    coverage: Option<Coverage>,
+    /// Tests defined with `test:`.
+    tests: Vec<ScriptTest>,
 }
 
 impl<State> Interpreter<State> {
@@ -95,13 +206,18 @@ impl<State> Interpreter<State> {
         let mut interpreter = Self {
             state,
             compiling: false,
+            compile_buffer: CompiledWord::default(),
+            immediate_words: HashSet::new(),
+            last_defined: None,
             break_loop: false,
             address_cache: AddressCache::uninitalized(),
             program_counter: 0,
             program: vec![],
+            current_word: None,
+            frames: vec![],
             program_counter_stack: vec![],
             program_debug_locations: vec![],
-            exit: false,
+            exit_code: None,
             read_mode: ReadMode::Off,
             repl_mode: false,
             ram: HashMap::new(),
@@ -109,6 +225,17 @@ impl<State> Interpreter<State> {
             next_address: Address::default(),
             documentation_table: HashMap::new(),
             name_table: HashMap::new(),
+            rng: Rng::default(),
+            redefinition_policy: RedefinitionPolicy::default(),
+            redefinition_callback: None,
+            sealed_words: HashSet::new(),
+            retired_names: HashMap::new(),
+            output: Mutex::new(Box::new(std::io::stdout())),
+            debugger: Debugger::default(),
+            trace_hook: None,
+            profile: None,
+            coverage: None,
+            tests: vec![],
         };
 
         interpreter.register_builtins();
@@ -120,12 +247,54 @@ impl<State> Interpreter<State> {
 
     /// Returns whether the interpreter should exit.
     pub fn exit(&self) -> bool {
-        self.exit
+        self.exit_code.is_some()
+    }
+
+    /// Returns the status code the program exited with, if it called `exit`.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Clear the status code of `exit`, so code can run again.
+    /// `evaluate` does this before running a program.
+    pub fn clear_exit(&mut self) {
+        self.exit_code = None;
+    }
+
+    /// Seed the random number generator.
+    /// The same seed always gives the same sequence of random numbers.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    /// Seed the random number generator from OS entropy.
+    /// Random numbers will not be reproducible unless the state is read back with `rng_state`.
+    pub fn seed_from_entropy(&mut self) {
+        self.rng = Rng::from_entropy();
+    }
+
+    /// Get the state of the random number generator, e.g. to include in a snapshot.
+    pub fn rng_state(&self) -> u64 {
+        self.rng.state()
+    }
+
+    /// Restore the state of the random number generator previously returned by `rng_state`.
+    pub fn set_rng_state(&mut self, state: u64) {
+        self.rng = Rng::new(state);
     }
 
     /// Evaluate a program.
+    /// The status code of a previous `exit` is cleared first.
     pub fn evaluate(&mut self, code: &str, path: Option<PathBuf>) -> Result<(), Err> {
+        self.clear_exit();
+        let start = self.program.len();
         self.load_program(code, path)?;
+        if let Some(err) = Self::undefined_words_error(self.undefined_words(start)) {
+            self.program.truncate(start);
+            self.program_debug_locations.truncate(start);
+            return Err(err);
+        }
+        self.cover_program(start);
         self.execute()?;
         Ok(())
     }
@@ -139,6 +308,16 @@ impl<State> Interpreter<State> {
             }
         }
 
+        // Check if the address is a test
+        if let Some(name) = self.test_name(address) {
+            return name.to_string();
+        }
+
+        // Check if the address is an old definition of a redefined word
+        if let Some(name) = self.retired_names.get(&address) {
+            return name.clone();
+        }
+
         // Didn't find it, so if we have something in ram that contains the address, use that
         for (k, value) in &self.ram {
             if let Some(addr) = value.address() {
@@ -152,6 +331,11 @@ impl<State> Interpreter<State> {
         format!("@UNKNOWN-{:?}", address)
     }
 
+    /// The stack, top last.
+    pub fn stack(&self) -> &[StackValue] {
+        &self.stack
+    }
+
     /// Pop a value from the stack.
     pub fn pop(&mut self) -> Result<StackValue, Err> {
         self.stack
@@ -193,6 +377,26 @@ impl<State> Interpreter<State> {
         }
     }
 
+    /// Push an execution token onto the stack.
+    pub fn push_execution_token(&mut self, address: Address) {
+        self.stack.push(StackValue::ExecutionToken(address));
+    }
+
+    /// Pop an execution token from the stack.
+    pub fn pop_execution_token(&mut self) -> Result<Address, Err> {
+        match self.pop()? {
+            StackValue::ExecutionToken(address) => Ok(address),
+            StackValue::Address(address) => Err((
+                format!(
+                    "Expected an execution token, got the data address {}",
+                    self.get_name(address)
+                ),
+                self.location(),
+            )),
+            _ => Err(("Expected an execution token".to_string(), self.location())),
+        }
+    }
+
     /// Push a string onto the stack.
     pub fn push_string(&mut self, string: String) {
         self.stack.push(StackValue::Value(Value::String(string)));
@@ -206,41 +410,41 @@ impl<State> Interpreter<State> {
         }
     }
 
-    /// Start a REPL.
-    pub fn start_repl(&mut self) -> Result<(), Err> {
-        self.repl_mode = true;
-        println!("Use 'repl-exit' to exit REPL mode.");
-
-        while !self.exit && self.repl_mode {
-            // Read input from stdin
-            let mut input = String::new();
-            print!("> ");
-            std::io::stdout().flush().unwrap();
-            std::io::stdin().read_line(&mut input).unwrap();
-
-            let input = format!("{} print-stack", input.trim());
-
-            // Evaluate input
-            match self.evaluate(&input, None) {
-                Ok(_) => (),
-                Err((e, location)) => println!("{}: {}", location, e),
-            }
+    /// Register a built-in function to be used in the interpreter.
+    /// If the word can't be redefined due to the redefinition policy or sealing,
+    /// a warning is written to the output and the existing definition is kept.
+    /// Use `try_register_builtin` to handle the error instead.
+    pub fn register_builtin(
+        &mut self,
+        name: &str,
+        stack_modification: &str,
+        documentation: &str,
+        example: &str,
+        func: BuiltIn<State>,
+    ) {
+        if let Err((e, location)) =
+            self.try_register_builtin(name, stack_modification, documentation, example, func)
+        {
+            let warning = format!("Warning: {}: {}\n", location, e);
+            // A warning that can't be written must not stop the registration of other words.
+            let _ = self.write_output(&warning);
         }
-        Ok(())
     }
 
     /// Register a built-in function to be used in the interpreter.
-    pub fn register_builtin(
+    /// Returns an error if the word can't be redefined due to the redefinition policy or sealing.
+    pub fn try_register_builtin(
         &mut self,
         name: &str,
         stack_modification: &str,
         documentation: &str,
         example: &str,
         func: BuiltIn<State>,
-    ) {
+    ) -> Result<(), Err> {
         let address = self.get_address(name);
+        self.define_word(address, RamValue::BuiltIn(func))?;
         self.register_documentation(address, stack_modification, documentation, example);
-        self.ram.insert(address, RamValue::BuiltIn(func));
+        Ok(())
     }
 
     /// Register documentation for a word.
@@ -251,41 +455,40 @@ impl<State> Interpreter<State> {
         documentation: &str,
         example: &str,
     ) {
-        let stack_modification = if stack_modification.is_empty() {
-            "".to_string()
-        } else {
-            format!("\t( {} )", stack_modification.replace("...", "..").trim())
-        };
-
-        let example = if example.is_empty() {
-            "".to_string()
-        } else {
-            format!("\n\tExample '{}'", example)
+        let documentation = WordDocumentation {
+            stack_modification: stack_modification.replace("...", "..").trim().to_string(),
+            documentation: documentation.trim().to_string(),
+            example: example.to_string(),
         };
-        let documentation = documentation.trim();
-        let documentation = format!("{stack_modification}\n\t{}{}", documentation, example);
-
         self.documentation_table.insert(address, documentation);
     }
 
-    /// Print the documentation for all words.
-    pub fn print_documentation(&self) {
-        println!("Documentation:");
+    /// Returns the documentation for all words.
+    pub fn documentation(&self) -> String {
+        let mut buffer = String::from("Documentation:\n");
         let mut names = self.name_table.keys().cloned().collect::<Vec<_>>();
         names.sort();
         for name in names {
             let address = self.name_table[&name];
             if let Some(documentation) = self.documentation_table.get(&address) {
-                println!("{}  {}\n", name, documentation);
+                buffer.push_str(&format!("{}  {}\n\n", name, documentation));
             }
         }
-        println!();
+        buffer.push('\n');
+        buffer
+    }
+
+    /// Print the documentation for all words to the output.
+    /// Errors writing to the output are ignored.
+    pub fn print_documentation(&self) {
+        let documentation = self.documentation();
+        let _ = self.write_output(&documentation);
     }
 
     /// Get the address of a name.
     pub(crate) fn get_address(&mut self, name: &str) -> Address {
         if let Some(address) = self.name_table.get(name) {
-            address.clone()
+            *address
         } else {
             let address = self.next_address;
             self.next_address = self.next_address.next();
@@ -359,7 +562,7 @@ mod tests {
         let mut interpreter = Interpreter::new(());
         interpreter.push_number(0.1);
         let value = interpreter.pop_bool().unwrap();
-        assert_eq!(value, true);
+        assert!(value);
     }
 
     #[test]
@@ -367,7 +570,7 @@ mod tests {
         let mut interpreter = Interpreter::new(());
         interpreter.push_number(0.0);
         let value = interpreter.pop_bool().unwrap();
-        assert_eq!(value, false);
+        assert!(!value);
     }
 
     #[test]
@@ -410,11 +613,31 @@ mod tests {
     fn pop_address_returns_value() {
         let mut interpreter = Interpreter::new(());
         let address = interpreter.next_address;
-        interpreter.push_address(address.clone());
+        interpreter.push_address(address);
         let value = interpreter.pop_address().unwrap();
         assert_eq!(value, address);
     }
 
+    #[test]
+    fn pop_execution_token_returns_err_if_data_address() {
+        let mut interpreter = Interpreter::new(());
+        let address = interpreter.get_address("life");
+        interpreter.push_address(address);
+        let err = interpreter.pop_execution_token().unwrap_err();
+        assert_eq!(
+            err.0,
+            "Expected an execution token, got the data address life"
+        );
+    }
+
+    #[test]
+    fn pop_execution_token_returns_value() {
+        let mut interpreter = Interpreter::new(());
+        let address = interpreter.get_address("dup");
+        interpreter.push_execution_token(address);
+        assert_eq!(interpreter.pop_execution_token().unwrap(), address);
+    }
+
     #[test]
     fn parse_string_returns_err_if_not_closed() {
         let code = r#""hello"#;
@@ -435,7 +658,7 @@ mod tests {
     }
 
     #[test]
-    fn var_only_creates_if_not_already_in_ram() {
+    fn var_redefines_existing_word() {
         let mut interpreter = Interpreter::new(());
         let address = interpreter.next_address;
         interpreter.evaluate("var test", None).unwrap();
@@ -445,15 +668,28 @@ mod tests {
         interpreter.evaluate("var test", None).unwrap();
 
         let second_call = interpreter.ram.get(&address).unwrap().clone();
-        assert_eq!(first_call, second_call);
+        assert_ne!(first_call, second_call);
+
+        interpreter.set_redefinition_policy(RedefinitionPolicy::Error);
+        let result = interpreter.evaluate("var test", None);
+        assert_eq!(result.unwrap_err().0, "Unable to redefine existing word: test");
     }
 
-    // #[test]
-    // fn parse_string_puts_escaped_string_on_stack() {
-    //     let mut interpreter = Interpreter::new(());
-    //     let code = "\"hello\n \\\" \"";
-    //     interpreter.evaluate(code, None).unwrap();
-    //     let value = interpreter.pop_string().unwrap();
-    //     assert_eq!(value.as_str(), "hello\n \" ");
-    // }
+    #[test]
+    fn parse_string_puts_escaped_string_on_stack() {
+        let mut interpreter = Interpreter::new(());
+        let code = "\"hello\n \\\" \"";
+        interpreter.evaluate(code, None).unwrap();
+        let value = interpreter.pop_string().unwrap();
+        assert_eq!(value.as_str(), "hello\n \" ");
+    }
+
+    #[test]
+    fn parse_raw_string_puts_string_on_stack() {
+        let mut interpreter = Interpreter::new(());
+        let code = "r#\"line \"one\"\nline \\two\"#";
+        interpreter.evaluate(code, None).unwrap();
+        let value = interpreter.pop_string().unwrap();
+        assert_eq!(value.as_str(), "line \"one\"\nline \\two");
+    }
 }
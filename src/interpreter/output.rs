@@ -0,0 +1,71 @@
+use super::*;
+use std::sync::{Arc, MutexGuard};
+
+/// An output sink that collects everything written to it.
+/// Clones share the same buffer, so one clone can be given to `Interpreter::set_output`
+/// and another used to read the output, e.g. to show it in a game log.
+#[derive(Debug, Clone, Default)]
+pub struct SharedOutput {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl SharedOutput {
+    /// Create a new, empty output buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns everything written so far.
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buffer()).to_string()
+    }
+
+    /// Returns everything written so far and clears the buffer.
+    pub fn take(&self) -> String {
+        let bytes = std::mem::take(&mut *self.buffer());
+        String::from_utf8_lossy(&bytes).to_string()
+    }
+
+    /// Lock the buffer, ignoring poisoning since a byte buffer is always valid.
+    fn buffer(&self) -> MutexGuard<'_, Vec<u8>> {
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<State> Interpreter<State> {
+    /// Set where the printing words write to. Defaults to stdout.
+    pub fn set_output(&mut self, output: Box<dyn Write + Send>) {
+        *self.output.get_mut().unwrap_or_else(|e| e.into_inner()) = output;
+    }
+
+    /// Replace the output, returning the previous one.
+    pub(crate) fn replace_output(
+        &mut self,
+        output: Box<dyn Write + Send>,
+    ) -> Box<dyn Write + Send> {
+        std::mem::replace(
+            self.output.get_mut().unwrap_or_else(|e| e.into_inner()),
+            output,
+        )
+    }
+
+    /// Write text to the output.
+    pub(crate) fn write_output(&self, text: &str) -> Result<(), Err> {
+        let mut output = self.output.lock().unwrap_or_else(|e| e.into_inner());
+        output
+            .write_all(text.as_bytes())
+            .and_then(|_| output.flush())
+            .map_err(|e| (format!("Unable to write output: {}", e), self.location()))
+    }
+}
@@ -0,0 +1,327 @@
+use super::*;
+use std::time::{Duration, Instant};
+
+/// Name of the root frame in folded stacks, for instructions run outside of words.
+const TOP_LEVEL: &str = "(top level)";
+
+/// Statistics of a word collected by the profiler.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordProfile {
+    /// Name of the word.
+    pub name: String,
+    /// Number of times the word was called.
+    pub calls: u64,
+    /// Instructions run by the word and the words it called.
+    pub inclusive_instructions: u64,
+    /// Instructions run by the word itself.
+    pub exclusive_instructions: u64,
+    /// Time spent in the word and the words it called.
+    pub inclusive_time: Duration,
+    /// Time spent in the word itself.
+    pub exclusive_time: Duration,
+}
+
+/// A call of a word that has not returned yet.
+#[derive(Debug)]
+struct ProfileFrame {
+    address: Address,
+    /// Names of the words on the call stack, separated by `;`.
+    path: String,
+    start: Instant,
+    /// Instructions run before the call.
+    instructions: u64,
+    /// Time spent in words called by this word.
+    child_time: Duration,
+    /// Whether the word was not already running, so recursive calls are only counted once.
+    outermost: bool,
+}
+
+/// A profile of the words run while profiling, see `Interpreter::start_profiling`.
+#[derive(Debug, Default)]
+pub struct Profile {
+    words: HashMap<Address, WordProfile>,
+    /// Instructions run with each call stack.
+    folded: HashMap<String, u64>,
+    frames: Vec<ProfileFrame>,
+    /// Instructions run in total.
+    instructions: u64,
+}
+
+impl Profile {
+    /// Statistics of all words that were called, slowest first.
+    pub fn words(&self) -> Vec<&WordProfile> {
+        let mut words: Vec<_> = self.words.values().collect();
+        words.sort_by(|a, b| {
+            b.inclusive_time
+                .cmp(&a.inclusive_time)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        words
+    }
+
+    /// Statistics of a word, if it was called.
+    pub fn word(&self, name: &str) -> Option<&WordProfile> {
+        self.words.values().find(|word| word.name == name)
+    }
+
+    /// Instructions run in total.
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    /// Returns a table of all words that were called, slowest first.
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "{:<24} {:>10} {:>12} {:>12} {:>12} {:>12}\n",
+            "word", "calls", "incl instr", "excl instr", "incl ms", "excl ms"
+        );
+        for word in self.words() {
+            report.push_str(&format!(
+                "{:<24} {:>10} {:>12} {:>12} {:>12.3} {:>12.3}\n",
+                word.name,
+                word.calls,
+                word.inclusive_instructions,
+                word.exclusive_instructions,
+                word.inclusive_time.as_secs_f64() * 1000.0,
+                word.exclusive_time.as_secs_f64() * 1000.0
+            ));
+        }
+        report
+    }
+
+    /// Returns the instructions run per call stack in the folded format read by flamegraph tools,
+    /// e.g. `(top level);update;move 12`.
+    pub fn folded(&self) -> String {
+        let mut lines: Vec<_> = self
+            .folded
+            .iter()
+            .map(|(path, count)| format!("{} {}\n", path, count))
+            .collect();
+        lines.sort();
+        lines.concat()
+    }
+
+    /// Count an instruction run by the innermost word.
+    fn count_instruction(&mut self) {
+        self.instructions += 1;
+        let path = self
+            .frames
+            .last()
+            .map(|frame| frame.path.as_str())
+            .unwrap_or(TOP_LEVEL);
+        match self.folded.get_mut(path) {
+            Some(count) => *count += 1,
+            None => {
+                self.folded.insert(path.to_string(), 1);
+            }
+        }
+        if let Some(frame) = self.frames.last() {
+            if let Some(word) = self.words.get_mut(&frame.address) {
+                word.exclusive_instructions += 1;
+            }
+        }
+    }
+
+    /// Start a call of a word.
+    fn enter(&mut self, address: Address, name: String) {
+        let outermost = !self.frames.iter().any(|frame| frame.address == address);
+        let parent = self
+            .frames
+            .last()
+            .map(|frame| frame.path.as_str())
+            .unwrap_or(TOP_LEVEL);
+        let path = format!("{};{}", parent, name);
+
+        let word = self.words.entry(address).or_insert_with(|| WordProfile {
+            name,
+            calls: 0,
+            inclusive_instructions: 0,
+            exclusive_instructions: 0,
+            inclusive_time: Duration::ZERO,
+            exclusive_time: Duration::ZERO,
+        });
+        word.calls += 1;
+
+        self.frames.push(ProfileFrame {
+            address,
+            path,
+            start: Instant::now(),
+            instructions: self.instructions,
+            child_time: Duration::ZERO,
+            outermost,
+        });
+    }
+
+    /// Finish the innermost call.
+    fn exit(&mut self) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        let elapsed = frame.start.elapsed();
+        if let Some(parent) = self.frames.last_mut() {
+            parent.child_time += elapsed;
+        }
+
+        if let Some(word) = self.words.get_mut(&frame.address) {
+            word.exclusive_time += elapsed.saturating_sub(frame.child_time);
+            if frame.outermost {
+                word.inclusive_time += elapsed;
+                word.inclusive_instructions += self.instructions - frame.instructions;
+            }
+        }
+    }
+}
+
+impl<State> Interpreter<State> {
+    /// Start collecting a profile of all words called, discarding the previous profile.
+    pub fn start_profiling(&mut self) {
+        self.profile = Some(Profile::default());
+    }
+
+    /// Stop profiling and return the profile.
+    pub fn stop_profiling(&mut self) -> Option<Profile> {
+        self.profile.take()
+    }
+
+    /// The profile collected so far, if profiling.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Execute an instruction, recording it in the profile.
+    /// Calls are counted as instructions of the called word.
+    pub(crate) fn execute_profiled(&mut self, instruction: Instruction) -> Result<(), Err> {
+        let Some(address) = self.profiled_call(&instruction) else {
+            if let Some(profile) = &mut self.profile {
+                profile.count_instruction();
+            }
+            return self.dispatch_instruction(instruction);
+        };
+
+        let name = self.get_name(address);
+        if let Some(profile) = &mut self.profile {
+            profile.enter(address, name);
+            profile.count_instruction();
+        }
+        let result = self.dispatch_instruction(instruction);
+        if let Some(profile) = &mut self.profile {
+            profile.exit();
+        }
+        result
+    }
+
+    /// Returns the word the instruction calls, if it calls one that is profiled.
+    /// Control flow words are not calls, so the instructions they run count for the enclosing word.
+    fn profiled_call(&self, instruction: &Instruction) -> Option<Address> {
+        let address = instruction.get_address()?;
+        if self.read_mode != ReadMode::Off && address != self.address_cache.read_mode_end {
+            return None;
+        }
+        if self.address_cache.is_control_flow(address) {
+            return None;
+        }
+        match self.ram.get(&address)? {
+            RamValue::Compiled(_) | RamValue::BuiltIn(_) | RamValue::Record(_) => Some(address),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: &str = ": double \"\" \"\" \"\" 2 * ;\n: quad \"\" \"\" \"\" double double ;\n: noop \"\" \"\" \"\" ;";
+
+    /// Define the words, then profile the code.
+    fn profile(words: &str, code: &str) -> Profile {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate(words, None).unwrap();
+        interpreter.start_profiling();
+        interpreter.evaluate(code, None).unwrap();
+        interpreter.stop_profiling().unwrap()
+    }
+
+    #[test]
+    fn counts_calls_and_instructions() {
+        let profile = profile(WORDS, "3 quad quad");
+
+        let quad = profile.word("quad").unwrap();
+        assert_eq!(quad.calls, 2);
+        assert_eq!(quad.exclusive_instructions, 2);
+        assert_eq!(quad.inclusive_instructions, 14);
+
+        let double = profile.word("double").unwrap();
+        assert_eq!(double.calls, 4);
+        assert_eq!(double.exclusive_instructions, 8);
+        assert_eq!(double.inclusive_instructions, 12);
+
+        let multiply = profile.word("*").unwrap();
+        assert_eq!(multiply.calls, 4);
+        assert_eq!(multiply.inclusive_instructions, 4);
+        assert_eq!(profile.instructions(), 15);
+        assert!(quad.inclusive_time >= double.exclusive_time);
+        assert!(profile.word("noop").is_none());
+    }
+
+    #[test]
+    fn recursive_calls_are_counted_once_inclusively() {
+        let words = ": countdown \"\" \"\" \"\" dup 0 < if 1 - countdown end ;";
+        let profile = profile(words, "2 countdown");
+        let countdown = profile.word("countdown").unwrap();
+        assert_eq!(countdown.calls, 3);
+        assert_eq!(countdown.inclusive_instructions, profile.instructions() - 1);
+    }
+
+    #[test]
+    fn folded_stacks() {
+        let profile = profile(WORDS, "3 quad quad");
+        let folded = profile.folded();
+        assert_eq!(
+            folded,
+            "(top level) 1\n(top level);quad 2\n(top level);quad;double 8\n(top level);quad;double;* 4\n"
+        );
+    }
+
+    #[test]
+    fn control_flow_counts_for_enclosing_word() {
+        let words = ": work \"\" \"\" \"\" 0 begin 1 + dup 3 == if break end loop 1 if 2 + end ;";
+        let profile = profile(words, "work");
+        let work = profile.word("work").unwrap();
+        assert_eq!(work.exclusive_instructions, 19);
+        assert_eq!(work.inclusive_instructions, profile.instructions());
+        assert_eq!(profile.word("+").unwrap().calls, 4);
+        for word in ["if", "end", "begin", "break", "loop"] {
+            assert!(profile.word(word).is_none(), "{}", word);
+        }
+        assert_eq!(
+            profile.folded(),
+            "(top level);work 19\n(top level);work;+ 4\n(top level);work;== 3\n(top level);work;dup 3\n"
+        );
+    }
+
+    #[test]
+    fn report_lists_words() {
+        let report = profile(WORDS, "3 quad quad").report();
+        let lines: Vec<_> = report.lines().collect();
+        assert!(lines[0].starts_with("word"));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("double") && line.split_whitespace().nth(1) == Some("4")));
+    }
+
+    #[test]
+    fn errors_keep_profile_consistent() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.start_profiling();
+        interpreter
+            .evaluate(": fail \"\" \"\" \"\" drop ;\nfail", None)
+            .unwrap_err();
+        interpreter.recover();
+        interpreter.evaluate("1 2 +", None).unwrap();
+        let profile = interpreter.stop_profiling().unwrap();
+        assert_eq!(profile.word("fail").unwrap().calls, 1);
+        assert_eq!(profile.word("+").unwrap().calls, 1);
+        assert!(profile.folded().contains("(top level);+ 1\n"));
+    }
+}
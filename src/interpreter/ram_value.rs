@@ -1,17 +1,29 @@
 use super::*;
 
+#[allow(unpredictable_function_pointer_comparisons)]
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum RamValue<State> {
     BuiltIn(BuiltIn<State>),
     Value(Value),
     Address(Address),
-    Compiled(Vec<Instruction>),
+    Compiled(CompiledWord),
+    /// An executable reference to a word, e.g. stored in a variable.
+    ExecutionToken(Address),
+    /// A constant defined with `constant`. Cannot be changed.
+    Constant(StackValue),
+    /// A value defined with `value`. Can be changed with `to`.
+    NamedValue(StackValue),
+    /// A word declared with `defer`. Calls the word it was set to with `is`.
+    Deferred(Option<Address>),
+    /// A word generated by `struct` for a record type.
+    Record(RecordWord),
 }
 impl<State> From<StackValue> for RamValue<State> {
     fn from(value: StackValue) -> Self {
         match value {
             StackValue::Address(address) => RamValue::Address(address),
             StackValue::Value(value) => RamValue::Value(value),
+            StackValue::ExecutionToken(address) => RamValue::ExecutionToken(address),
         }
     }
 }
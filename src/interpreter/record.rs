@@ -0,0 +1,233 @@
+use super::*;
+use std::sync::Arc;
+
+/// A record type declared with `struct`.
+/// The field names are stored once per type and shared by all records of the type.
+#[derive(Debug, PartialEq)]
+pub struct RecordType {
+    name: String,
+    fields: Vec<String>,
+}
+
+impl RecordType {
+    /// The name of the type.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of the fields, in declaration order.
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+}
+
+/// A record, an instance of a record type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    record_type: Arc<RecordType>,
+    values: Vec<StackValue>,
+}
+
+impl Record {
+    /// The type of the record.
+    pub fn record_type(&self) -> &RecordType {
+        &self.record_type
+    }
+
+    /// The values of the fields, in declaration order.
+    pub fn values(&self) -> &[StackValue] {
+        &self.values
+    }
+
+    /// The values of the fields, to update them in place.
+    pub(crate) fn values_mut(&mut self) -> &mut [StackValue] {
+        &mut self.values
+    }
+
+    /// Get the value of a field by name.
+    pub fn get(&self, field: &str) -> Option<&StackValue> {
+        let idx = self.record_type.fields.iter().position(|f| f == field)?;
+        self.values.get(idx)
+    }
+}
+
+/// Words generated by `struct` for a record type.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RecordWord {
+    /// Constructs a record from the values of its fields.
+    Make(Arc<RecordType>),
+    /// Gets the value of the field at the index.
+    Get(Arc<RecordType>, usize),
+    /// Sets the value of the field at the index.
+    Set(Arc<RecordType>, usize),
+    /// Checks if a value is a record of the type.
+    Is(Arc<RecordType>),
+}
+
+impl<State> Interpreter<State> {
+    /// Declare a record type and define its constructor, accessors, setters and type predicate.
+    /// Nothing is defined unless every generated word can be defined.
+    pub(crate) fn define_struct(&mut self, name: &str, fields: Vec<String>) -> Result<(), Err> {
+        if BUILT_IN_TYPE_NAMES.contains(&name) {
+            return Err((
+                format!("Struct name is a built-in type: {}", name),
+                self.location(),
+            ));
+        }
+        let mut words = vec![format!("make-{}", name), format!("{}?", name)];
+        for field in fields.iter() {
+            words.push(format!("{}.{}", name, field));
+            words.push(format!("{}.{}!", name, field));
+        }
+        for word in words.iter() {
+            if let Some(&address) = self.name_table.get(word) {
+                self.check_redefinition(address)?;
+            }
+        }
+
+        let record_type = Arc::new(RecordType {
+            name: name.to_string(),
+            fields,
+        });
+        let field_list = record_type.fields.join(" ");
+
+        let make = self.get_address(&format!("make-{}", name));
+        self.define_word(
+            make,
+            RamValue::Record(RecordWord::Make(record_type.clone())),
+        )?;
+        self.register_documentation(
+            make,
+            &format!("{} -- {}", field_list, name),
+            &format!("Creates a {} record from the values of its fields.", name),
+            "",
+        );
+
+        let is = self.get_address(&format!("{}?", name));
+        self.define_word(is, RamValue::Record(RecordWord::Is(record_type.clone())))?;
+        self.register_documentation(
+            is,
+            "any -- 0|1",
+            &format!(
+                "Checks if a value is a {} record. Puts 1 on the stack if it is, 0 otherwise.",
+                name
+            ),
+            "",
+        );
+
+        for (idx, field) in record_type.fields.iter().enumerate() {
+            let get = self.get_address(&format!("{}.{}", name, field));
+            self.define_word(
+                get,
+                RamValue::Record(RecordWord::Get(record_type.clone(), idx)),
+            )?;
+            self.register_documentation(
+                get,
+                &format!("{} -- any", name),
+                &format!("Gets the {} field of a {} record.", field, name),
+                "",
+            );
+
+            let set = self.get_address(&format!("{}.{}!", name, field));
+            self.define_word(
+                set,
+                RamValue::Record(RecordWord::Set(record_type.clone(), idx)),
+            )?;
+            self.register_documentation(
+                set,
+                &format!("{} any -- {}", name, name),
+                &format!("Sets the {} field of a {} record.", field, name),
+                "",
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Run a word generated by `struct`.
+    pub(crate) fn run_record_word(&mut self, word: RecordWord) -> Result<(), Err> {
+        match word {
+            RecordWord::Make(record_type) => {
+                let count = record_type.fields.len();
+                if count > self.stack.len() {
+                    return Err((
+                        format!(
+                            "make-{} expects {} values, got {}",
+                            record_type.name,
+                            count,
+                            self.stack.len()
+                        ),
+                        self.location(),
+                    ));
+                }
+                let values = self.stack.split_off(self.stack.len() - count);
+                self.push_record(Record {
+                    record_type,
+                    values,
+                });
+            }
+            RecordWord::Get(record_type, idx) => {
+                let mut record = self.pop_record_of(&record_type)?;
+                self.stack.push(record.values.swap_remove(idx));
+            }
+            RecordWord::Set(record_type, idx) => {
+                let value = self.pop()?;
+                let mut record = self.pop_record_of(&record_type)?;
+                record.values[idx] = value;
+                self.push_record(record);
+            }
+            RecordWord::Is(record_type) => {
+                let is_record = matches!(
+                    self.pop()?,
+                    StackValue::Value(Value::Record(record)) if Arc::ptr_eq(&record.record_type, &record_type)
+                );
+                self.push_number(is_record as u8 as Number);
+            }
+        }
+        Ok(())
+    }
+
+    /// Push a record onto the stack.
+    pub fn push_record(&mut self, record: Record) {
+        self.stack.push(StackValue::Value(Value::Record(record)));
+    }
+
+    /// Pop a record from the stack.
+    pub fn pop_record(&mut self) -> Result<Record, Err> {
+        match self.pop()? {
+            StackValue::Value(Value::Record(record)) => Ok(record),
+            _ => Err(("Expected a record".to_string(), self.location())),
+        }
+    }
+
+    /// Pop a record of the given type from the stack.
+    fn pop_record_of(&mut self, record_type: &Arc<RecordType>) -> Result<Record, Err> {
+        match self.pop()? {
+            StackValue::Value(Value::Record(record))
+                if Arc::ptr_eq(&record.record_type, record_type) =>
+            {
+                Ok(record)
+            }
+            _ => Err((
+                format!("Expected record of type {}", record_type.name),
+                self.location(),
+            )),
+        }
+    }
+
+    /// Returns a record as it is displayed by `print-stack`.
+    pub(crate) fn stringify_record(&self, record: &Record) -> String {
+        let fields = record
+            .record_type
+            .fields
+            .iter()
+            .zip(&record.values)
+            .map(|(field, value)| format!("{}: {}", field, self.stringify_value(value)))
+            .collect::<Vec<_>>();
+        if fields.is_empty() {
+            format!("{} {{ }}", record.record_type.name)
+        } else {
+            format!("{} {{ {} }}", record.record_type.name, fields.join(" "))
+        }
+    }
+}
@@ -0,0 +1,345 @@
+use super::*;
+use std::collections::hash_map::Entry;
+
+/// What happens when an existing word is redefined.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RedefinitionPolicy {
+    /// Redefine the word.
+    #[default]
+    Allow,
+    /// Redefine the word and write a warning to the output.
+    Warn,
+    /// Return an error and keep the existing definition.
+    Error,
+}
+
+/// A type alias for the callback notified when a word is redefined.
+/// Receives the name of the word and the location of the redefinition.
+pub type RedefinitionCallback<State> = fn(&mut Interpreter<State>, &str, &Location);
+
+impl<State> Interpreter<State> {
+    /// Set the policy for redefining existing words.
+    pub fn set_redefinition_policy(&mut self, policy: RedefinitionPolicy) {
+        self.redefinition_policy = policy;
+    }
+
+    /// Set a callback that is notified every time a word is redefined.
+    pub fn set_redefinition_callback(&mut self, callback: Option<RedefinitionCallback<State>>) {
+        self.redefinition_callback = callback;
+    }
+
+    /// Seal a word so it can never be redefined, regardless of the redefinition policy.
+    pub fn seal(&mut self, name: &str) {
+        let address = self.get_address(name);
+        self.sealed_words.insert(address);
+    }
+
+    /// Seal all words that are currently defined, e.g. to prevent scripts from replacing built-ins.
+    pub fn seal_all(&mut self) {
+        self.sealed_words.extend(self.ram.keys().copied());
+    }
+
+    /// Returns whether a word is sealed.
+    pub fn is_sealed(&self, name: &str) -> bool {
+        self.name_table
+            .get(name)
+            .is_some_and(|address| self.sealed_words.contains(address))
+    }
+
+    /// Returns an error if an existing word can't be redefined
+    /// because it is sealed, a constant or the policy forbids it.
+    pub(crate) fn check_redefinition(&self, name: Address) -> Result<(), Err> {
+        if !self.ram.contains_key(&name) {
+            return Ok(());
+        }
+
+        let word = self.get_name(name);
+        if self.sealed_words.contains(&name) {
+            return Err((
+                format!("Unable to redefine sealed word: {}", word),
+                self.location(),
+            ));
+        }
+        // Constants can never be redefined, whatever the policy.
+        if let Some(RamValue::Constant(_)) = self.ram.get(&name) {
+            return Err((
+                format!("Constant already defined: {}", word),
+                self.location(),
+            ));
+        }
+        if self.redefinition_policy == RedefinitionPolicy::Error {
+            return Err((
+                format!("Unable to redefine existing word: {}", word),
+                self.location(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Define a word, applying the redefinition policy if it already exists.
+    /// Sealed words and constants are never redefined.
+    ///
+    /// Words compiled earlier and execution tokens taken earlier keep referencing the old definition.
+    /// The old definition is moved to a new address, and compiled words and execution tokens
+    /// in the interpreter are updated to point to it. Execution tokens held by the host are not updated.
+    pub(crate) fn define_word(&mut self, name: Address, value: RamValue<State>) -> Result<(), Err> {
+        if let Entry::Vacant(entry) = self.ram.entry(name) {
+            entry.insert(value);
+            return Ok(());
+        }
+
+        self.check_redefinition(name)?;
+        let word = self.get_name(name);
+        if self.redefinition_policy == RedefinitionPolicy::Warn {
+            let warning = format!("Warning: {}: redefining {}\n", self.location(), word);
+            // A warning that can't be written must not stop the redefinition.
+            let _ = self.write_output(&warning);
+        }
+
+        // Move the old definition to a new address.
+        let retired = self.next_address;
+        self.next_address = self.next_address.next();
+        if let Some(old) = self.ram.remove(&name) {
+            self.ram.insert(retired, old);
+        }
+        if let Some(documentation) = self.documentation_table.remove(&name) {
+            self.documentation_table.insert(retired, documentation);
+        }
+        if self.immediate_words.remove(&name) {
+            self.immediate_words.insert(retired);
+        }
+        self.retired_names.insert(retired, word.clone());
+
+        // Point previously compiled words and execution tokens at the old definition.
+        for ram_value in self.ram.values_mut() {
+            match ram_value {
+                RamValue::Compiled(compiled) => {
+                    for instruction in compiled.instructions.iter_mut() {
+                        if *instruction == Instruction::Address(name) {
+                            *instruction = Instruction::Address(retired);
+                        }
+                    }
+                }
+                RamValue::ExecutionToken(address) | RamValue::Deferred(Some(address))
+                    if *address == name =>
+                {
+                    *address = retired;
+                }
+                RamValue::Constant(value) | RamValue::NamedValue(value) => {
+                    retarget_execution_tokens(value, name, retired);
+                }
+                RamValue::Value(Value::Record(record)) => {
+                    for value in record.values_mut() {
+                        retarget_execution_tokens(value, name, retired);
+                    }
+                }
+                _ => {}
+            }
+        }
+        for value in self.stack.iter_mut() {
+            retarget_execution_tokens(value, name, retired);
+        }
+
+        self.ram.insert(name, value);
+
+        if let Some(callback) = self.redefinition_callback {
+            let location = self.location();
+            callback(self, &word, &location);
+        }
+
+        Ok(())
+    }
+}
+
+/// Point execution tokens for `name`, including those in records, at the `retired` definition.
+fn retarget_execution_tokens(value: &mut StackValue, name: Address, retired: Address) {
+    match value {
+        StackValue::ExecutionToken(address) if *address == name => *address = retired,
+        StackValue::Value(Value::Record(record)) => {
+            for value in record.values_mut() {
+                retarget_execution_tokens(value, name, retired);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redefinition_is_allowed_by_default() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        : greet "" "" "" "hello" ;
+        : greet "" "" "" "hi" ;
+        greet
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "hi");
+    }
+
+    #[test]
+    fn redefinition_returns_err_with_error_policy() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.set_redefinition_policy(RedefinitionPolicy::Error);
+        let result = interpreter.evaluate(": dup \"\" \"\" \"\" 1 ;", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Unable to redefine existing word: dup"
+        );
+
+        interpreter.reset_program();
+        interpreter.evaluate("2 dup", None).unwrap();
+        assert_eq!(interpreter.stack, [2.0.into(), 2.0.into()]);
+    }
+
+    #[test]
+    fn redefinition_writes_warning_to_output_with_warn_policy() {
+        let mut interpreter = Interpreter::new(());
+        let output = SharedOutput::new();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_redefinition_policy(RedefinitionPolicy::Warn);
+        interpreter
+            .evaluate(": a \"\" \"\" \"\" 1 ;\n: a \"\" \"\" \"\" 2 ;\na", None)
+            .unwrap();
+        assert_eq!(output.contents(), "Warning: stdin:2:3: redefining a\n");
+        assert_eq!(interpreter.pop_number().unwrap(), 2.0);
+    }
+
+    #[test]
+    fn redefinition_of_builtin_returns_err_with_error_policy() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.set_redefinition_policy(RedefinitionPolicy::Error);
+        let result = interpreter.try_register_builtin("+", "", "", "", |_| Ok(()));
+        assert_eq!(result.unwrap_err().0, "Unable to redefine existing word: +");
+    }
+
+    #[test]
+    fn registering_sealed_builtin_writes_warning_and_keeps_definition() {
+        let mut interpreter = Interpreter::new(());
+        let output = SharedOutput::new();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.seal("+");
+        interpreter.register_builtin("+", "", "", "", |_| Ok(()));
+        assert_eq!(output.contents(), "Warning: stdin:1:1: Unable to redefine sealed word: +\n");
+        interpreter.evaluate("1 2 +", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 3.0);
+    }
+
+    #[test]
+    fn constants_cannot_be_redefined() {
+        for code in [
+            ": answer \"\" \"\" \"\" 1 ;",
+            "defer answer",
+            "var answer",
+            "2 value answer",
+        ] {
+            let mut interpreter = Interpreter::new(());
+            interpreter.evaluate("42 constant answer", None).unwrap();
+            let result = interpreter.evaluate(code, None);
+            assert_eq!(
+                result.unwrap_err().0,
+                "Constant already defined: answer",
+                "{}",
+                code
+            );
+
+            interpreter.recover();
+            interpreter.evaluate("answer", None).unwrap();
+            assert_eq!(interpreter.pop_number().unwrap(), 42.0);
+        }
+    }
+
+    #[test]
+    fn struct_cannot_redefine_constant() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("42 constant make-pos struct pos { x }", None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Constant already defined: make-pos"
+        );
+        interpreter.recover();
+        interpreter.evaluate("make-pos", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 42.0);
+    }
+
+    #[test]
+    fn sealed_word_cannot_be_redefined() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.seal("+");
+        assert!(interpreter.is_sealed("+"));
+        assert!(!interpreter.is_sealed("-"));
+
+        let result = interpreter.evaluate(": + \"\" \"\" \"\" 1 ;", None);
+        assert_eq!(result.unwrap_err().0, "Unable to redefine sealed word: +");
+    }
+
+    #[test]
+    fn seal_all_seals_builtins() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.seal_all();
+        assert!(interpreter.is_sealed("dup"));
+        assert!(interpreter.is_sealed("+"));
+    }
+
+    #[test]
+    fn callback_is_notified_on_redefinition() {
+        let mut interpreter = Interpreter::new(vec![]);
+        interpreter.set_redefinition_callback(Some(|interpreter, name, location| {
+            interpreter.state.push(format!("{} {}", name, location));
+        }));
+        interpreter
+            .evaluate(": a \"\" \"\" \"\" 1 ;\n: a \"\" \"\" \"\" 2 ;", None)
+            .unwrap();
+        assert_eq!(interpreter.state, vec!["a stdin:2:3".to_string()]);
+    }
+
+    #[test]
+    fn earlier_words_keep_old_definition() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        : hp "" "" "" 10 ;
+        : show-hp "" "" "" hp ;
+        : hp "" "" "" 20 ;
+        show-hp hp
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.stack, [10.0.into(), 20.0.into()]);
+    }
+
+    #[test]
+    fn earlier_execution_tokens_keep_old_definition() {
+        let mut interpreter = Interpreter::new(());
+        let code = r#"
+        : hp "" "" "" 10 ;
+        ' hp
+        ' hp constant saved
+        defer show ' hp is show
+        struct holder { word }
+        ' hp make-holder value held
+        : hp "" "" "" 20 ;
+        execute saved execute show held holder.word execute hp
+        "#;
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(
+            interpreter.stack,
+            [10.0.into(), 10.0.into(), 10.0.into(), 10.0.into(), 20.0.into()]
+        );
+    }
+
+    #[test]
+    fn earlier_words_keep_old_builtin() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate(": double \"\" \"\" \"\" 2 * ;", None)
+            .unwrap();
+        interpreter.register_builtin("*", "n n -- n", "Broken multiply", "", |interpreter| {
+            interpreter.push_number(0.0);
+            Ok(())
+        });
+        interpreter.evaluate("3 double", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 6.0);
+    }
+}
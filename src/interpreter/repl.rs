@@ -0,0 +1,772 @@
+use super::*;
+use std::io::BufRead;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Words that open a block, with the word that closes it.
+const BLOCKS: [(&str, &str); 6] = [
+    (":", ";"),
+    ("test:", ";"),
+    ("if", "end"),
+    ("begin", "loop"),
+    ("[", "]"),
+    ("{", "}"),
+];
+
+/// Words that read the following word instead of running it.
+const READS_NEXT_WORD: [&str; 8] = [
+    "'", "postpone", "var", "constant", "value", "to", "defer", "is",
+];
+
+/// Name of the history file used by `start_repl`, stored in the home directory.
+const HISTORY_FILE: &str = ".stackr_history";
+
+/// Command that discards unfinished input.
+const RESET_COMMAND: &str = ":reset";
+
+/// Prompt shown while stopped in the debugger.
+const DEBUG_PROMPT: &str = "(debug) ";
+
+/// A read-eval-print loop.
+/// Input is buffered until definitions and blocks are balanced, so they can span lines.
+/// Entries are kept in a history that can be persisted to a file.
+#[derive(Debug, Default)]
+pub struct Repl {
+    /// Lines of the entry being entered.
+    buffer: String,
+    /// Previous entries, oldest first.
+    history: Vec<String>,
+    /// File the history is appended to.
+    history_path: Option<PathBuf>,
+}
+
+impl Repl {
+    /// Create a new REPL without persistent history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new REPL that loads its history from a file and appends new entries to it.
+    /// A missing file is created when the first entry is added.
+    pub fn with_history_file(path: PathBuf) -> std::io::Result<Self> {
+        let history = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents.lines().map(decode_history_entry).collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            buffer: String::new(),
+            history,
+            history_path: Some(path),
+        })
+    }
+
+    /// Previous entries, oldest first.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Whether an unfinished entry is buffered.
+    pub fn is_buffering(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    /// The prompt to show before reading the next line.
+    pub fn prompt(&self) -> &'static str {
+        if self.is_buffering() {
+            "... "
+        } else {
+            "> "
+        }
+    }
+
+    /// Write the greeting shown when the REPL starts.
+    pub fn greet<W: Write>(&self, mut output: W) -> std::io::Result<()> {
+        writeln!(output, "Use 'repl-exit' to exit REPL mode.")
+    }
+
+    /// Run the REPL until the input ends, `exit` is called or `repl-exit` is called.
+    /// Breakpoints stop at a debug prompt that reads commands such as `:step` from the same input.
+    /// Code runs on a separate thread while the input and output stay on the calling thread,
+    /// so they can be borrowed, e.g. locked stdin and stdout.
+    pub fn run<State: Send, R: BufRead, W: Write>(
+        &mut self,
+        interpreter: &mut Interpreter<State>,
+        input: R,
+        mut output: W,
+    ) -> std::io::Result<()> {
+        interpreter.repl_mode = true;
+        self.greet(&mut output)?;
+        if interpreter.exit() {
+            return Ok(());
+        }
+
+        let (entries, entry_receiver) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+        let (lines, line_receiver) = mpsc::channel();
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                run_entries(interpreter, entry_receiver, event_sender, line_receiver)
+            });
+            let session = Session {
+                input,
+                output,
+                entries,
+                events,
+                lines,
+            };
+            self.read_entries(session)
+        })
+    }
+
+    /// Read entries and write what running them printed until the REPL should stop.
+    fn read_entries<R: BufRead, W: Write>(
+        &mut self,
+        mut session: Session<R, W>,
+    ) -> std::io::Result<()> {
+        loop {
+            write!(session.output, "{}", self.prompt())?;
+            session.output.flush()?;
+
+            let Some(line) = session.read_line()? else {
+                return Ok(());
+            };
+            let Some(entry) = self.take_entry(&line)? else {
+                continue;
+            };
+            if session.entries.send(entry).is_err() {
+                return Ok(());
+            }
+            if !session.wait_for_entry()? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Add a line of input.
+    /// Once the buffered input is balanced it is evaluated.
+    /// Anything printed, followed by the stack or the error, is written to the output.
+    pub fn feed_line<State, W: Write>(
+        &mut self,
+        interpreter: &mut Interpreter<State>,
+        line: &str,
+        mut output: W,
+    ) -> std::io::Result<()> {
+        let Some(entry) = self.take_entry(line)? else {
+            return Ok(());
+        };
+
+        // Show the output of printing words in the session.
+        let printed = SharedOutput::new();
+        let previous_output = interpreter.replace_output(Box::new(printed.clone()));
+        let response = respond(interpreter, &entry);
+        interpreter.set_output(previous_output);
+        output.write_all(printed.take().as_bytes())?;
+        output.write_all(response.as_bytes())
+    }
+
+    /// Buffer a line of input. Returns the entry once it is balanced and not empty.
+    /// `:reset` discards the buffered input and is returned as an entry on its own.
+    fn take_entry(&mut self, line: &str) -> std::io::Result<Option<String>> {
+        if line.trim() == RESET_COMMAND {
+            self.clear_buffer();
+            return Ok(Some(RESET_COMMAND.to_string()));
+        }
+        self.buffer.push_str(line.trim_end_matches(['\r', '\n']));
+        self.buffer.push('\n');
+        if !is_complete(&self.buffer) {
+            return Ok(None);
+        }
+
+        let entry = std::mem::take(&mut self.buffer);
+        let entry = entry.trim();
+        if entry.is_empty() {
+            return Ok(None);
+        }
+        self.add_history(entry)?;
+        Ok(Some(entry.to_string()))
+    }
+
+    /// Discard the buffered, unfinished entry.
+    pub fn clear_buffer(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Add an entry to the history and append it to the history file.
+    fn add_history(&mut self, entry: &str) -> std::io::Result<()> {
+        self.history.push(entry.to_string());
+        if let Some(path) = &self.history_path {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            writeln!(file, "{}", encode_history_entry(entry))?;
+        }
+        Ok(())
+    }
+}
+
+/// Evaluate an entry or run a command, returning the stack or the error to show.
+fn respond<State>(interpreter: &mut Interpreter<State>, entry: &str) -> String {
+    if is_command(entry) {
+        return match run_command(interpreter, entry, None) {
+            CommandResult::Output(output) => output,
+            CommandResult::Resume(_) => "Not stopped in the debugger\n".to_string(),
+        };
+    }
+
+    match interpreter.evaluate(entry, None) {
+        Ok(()) if interpreter.exit() => String::new(),
+        Ok(()) => format!("{}\n", interpreter.stringify_stack()),
+        Err((message, location)) => {
+            interpreter.recover();
+            format!("{}: {}\n", location, message)
+        }
+    }
+}
+
+/// Messages from the thread running code to the thread with the input and output.
+enum SessionEvent {
+    /// Text to write to the output.
+    Output(Vec<u8>),
+    /// Read a line of input and send it back.
+    ReadLine,
+    /// The entry finished, with the stack or error to show and whether the REPL keeps running.
+    Done { response: String, running: bool },
+}
+
+/// The input and output of a running REPL, and the channels to the thread running code.
+struct Session<R, W> {
+    input: R,
+    output: W,
+    entries: Sender<String>,
+    events: Receiver<SessionEvent>,
+    /// Lines read for the debug prompt, None at the end of the input.
+    lines: Sender<Option<String>>,
+}
+
+impl<R: BufRead, W: Write> Session<R, W> {
+    /// Read a line of input, None at the end of the input.
+    fn read_line(&mut self) -> std::io::Result<Option<String>> {
+        let mut line = String::new();
+        match self.input.read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        }
+    }
+
+    /// Handle the events of the running entry until it finishes.
+    /// Returns whether the REPL keeps running.
+    fn wait_for_entry(&mut self) -> std::io::Result<bool> {
+        loop {
+            match self.events.recv() {
+                Ok(SessionEvent::Output(bytes)) => {
+                    self.output.write_all(&bytes)?;
+                    self.output.flush()?;
+                }
+                Ok(SessionEvent::ReadLine) => {
+                    let line = self.read_line()?;
+                    // The running code only stops waiting for lines when it ends.
+                    let _ = self.lines.send(line);
+                }
+                Ok(SessionEvent::Done { response, running }) => {
+                    self.output.write_all(response.as_bytes())?;
+                    return Ok(running);
+                }
+                Err(_) => return Ok(false),
+            }
+        }
+    }
+}
+
+/// Run the entries of a session until the session ends.
+/// Printed output and debugger stops are sent to the session as they happen.
+fn run_entries<State>(
+    interpreter: &mut Interpreter<State>,
+    entries: Receiver<String>,
+    events: Sender<SessionEvent>,
+    lines: Receiver<Option<String>>,
+) {
+    let previous_output = interpreter.replace_output(Box::new(EventWriter(events.clone())));
+    let previous_hook = interpreter.set_debug_hook(Some(debug_hook(events.clone(), lines)));
+    for entry in entries {
+        let response = respond(interpreter, &entry);
+        let running = !interpreter.exit() && interpreter.repl_mode;
+        let done = SessionEvent::Done { response, running };
+        if events.send(done).is_err() || !running {
+            break;
+        }
+    }
+    interpreter.set_output(previous_output);
+    interpreter.set_debug_hook(previous_hook);
+}
+
+/// Sends everything written to the session output.
+struct EventWriter(Sender<SessionEvent>);
+
+impl Write for EventWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .send(SessionEvent::Output(buf.to_vec()))
+            .map_err(|_| std::io::ErrorKind::BrokenPipe)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Help for the REPL commands.
+const COMMANDS: &str = "Commands:
+    :break <word|file:line>  Set a breakpoint
+    :delete [breakpoint]     Delete a breakpoint, or all breakpoints
+    :breakpoints             List breakpoints
+    :step, :s                Step into the next instruction
+    :next, :n                Step over the next instruction
+    :finish, :f              Run until the current word returns
+    :continue, :c            Run until the next breakpoint
+    :stack                   Show the stack
+    :rstack                  Show the return stack
+    :vars                    Show variables and values
+    :where                   Show where the debugger stopped
+    :reset                   Discard unfinished input
+";
+
+/// The result of a REPL command.
+enum CommandResult {
+    /// Text to show.
+    Output(String),
+    /// Continue running with the action. Only returned while stopped in the debugger.
+    Resume(DebugAction),
+}
+
+/// Returns whether the line is a REPL command such as `:break`, rather than code.
+fn is_command(line: &str) -> bool {
+    line.strip_prefix(':')
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(char::is_alphabetic)
+}
+
+/// Run a REPL command. `stop` is where the debugger stopped, if it did.
+fn run_command<State>(
+    interpreter: &mut Interpreter<State>,
+    line: &str,
+    stop: Option<&DebugStop>,
+) -> CommandResult {
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or_default();
+    let argument = words.next();
+    let output = match (command, argument) {
+        (":break", Some(breakpoint)) => {
+            let breakpoint = Breakpoint::parse(breakpoint);
+            let output = format!("Breakpoint set: {}\n", breakpoint);
+            interpreter.add_breakpoint(breakpoint);
+            output
+        }
+        (":break", None) => "Usage: :break <word|file:line>\n".to_string(),
+        (":delete", Some(breakpoint)) => {
+            let breakpoint = Breakpoint::parse(breakpoint);
+            if interpreter.remove_breakpoint(&breakpoint) {
+                format!("Breakpoint deleted: {}\n", breakpoint)
+            } else {
+                format!("No breakpoint: {}\n", breakpoint)
+            }
+        }
+        (":delete", None) => {
+            interpreter.clear_breakpoints();
+            "Breakpoints deleted\n".to_string()
+        }
+        (":breakpoints", _) if interpreter.breakpoints().is_empty() => {
+            "No breakpoints\n".to_string()
+        }
+        (":breakpoints", _) => interpreter
+            .breakpoints()
+            .iter()
+            .map(|breakpoint| format!("{}\n", breakpoint))
+            .collect(),
+        (":step" | ":s", _) if stop.is_none() => {
+            // Stop at the first instruction of the next entry.
+            interpreter.debug_step();
+            "Stopping at the next instruction\n".to_string()
+        }
+        (":step" | ":s", _) => return CommandResult::Resume(DebugAction::StepInto),
+        (":next" | ":n", _) => return CommandResult::Resume(DebugAction::StepOver),
+        (":finish" | ":f", _) => return CommandResult::Resume(DebugAction::StepOut),
+        (":continue" | ":c", _) => return CommandResult::Resume(DebugAction::Continue),
+        (":stack", _) => format!("{}\n", interpreter.stringify_stack()),
+        (":rstack", _) if interpreter.return_stack().is_empty() => {
+            "Return stack is empty\n".to_string()
+        }
+        (":rstack", _) => interpreter
+            .return_stack()
+            .into_iter()
+            .map(|(word, location)| {
+                format!("{} {}\n", word.as_deref().unwrap_or("top level"), location)
+            })
+            .collect(),
+        (":vars", _) if interpreter.variables().is_empty() => "No variables\n".to_string(),
+        (":vars", _) => interpreter
+            .variables()
+            .into_iter()
+            .map(|(name, value)| format!("{} = {}\n", name, interpreter.stringify_value(&value)))
+            .collect(),
+        (":where", _) => match stop {
+            Some(stop) => describe_stop(stop),
+            None => "Not stopped in the debugger\n".to_string(),
+        },
+        (RESET_COMMAND, _) => "Input discarded\n".to_string(),
+        (":help", _) => COMMANDS.to_string(),
+        _ => format!(
+            "Unknown command: {}. Use :help to list commands.\n",
+            command
+        ),
+    };
+    CommandResult::Output(output)
+}
+
+/// Describe where the debugger stopped.
+fn describe_stop(stop: &DebugStop) -> String {
+    let reason = match &stop.reason {
+        StopReason::Breakpoint(breakpoint) => format!("Breakpoint {}", breakpoint),
+        StopReason::Step => "Step".to_string(),
+    };
+    format!(
+        "{} at {} in {}: {}\n",
+        reason,
+        stop.location,
+        stop.word.as_deref().unwrap_or("top level"),
+        stop.instruction
+    )
+}
+
+/// Create a debug hook that reads commands from the session input until execution should continue.
+/// The end of the input continues running.
+fn debug_hook<State>(
+    events: Sender<SessionEvent>,
+    lines: Receiver<Option<String>>,
+) -> DebugHook<State> {
+    let mut output = EventWriter(events.clone());
+    Box::new(move |interpreter, stop| {
+        let _ = output.write_all(describe_stop(stop).as_bytes());
+        loop {
+            let _ = write!(output, "{}", DEBUG_PROMPT);
+            let line = match events.send(SessionEvent::ReadLine) {
+                Ok(()) => lines.recv().ok().flatten(),
+                Err(_) => None,
+            };
+            let Some(line) = line else {
+                return DebugAction::Continue;
+            };
+            let line = line.trim();
+            let response = if line.is_empty() {
+                continue;
+            } else if !is_command(line) {
+                "Stopped in the debugger, use :continue to run or :help to list commands\n"
+                    .to_string()
+            } else {
+                match run_command(interpreter, line, Some(stop)) {
+                    CommandResult::Output(response) => response,
+                    CommandResult::Resume(action) => return action,
+                }
+            };
+            let _ = output.write_all(response.as_bytes());
+        }
+    })
+}
+
+/// Encode an entry as a single line of the history file.
+fn encode_history_entry(entry: &str) -> String {
+    entry.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Decode a line of the history file.
+fn decode_history_entry(line: &str) -> String {
+    let mut entry = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                chars.next();
+                entry.push('\n');
+            }
+            ('\\', Some('\\')) => {
+                chars.next();
+                entry.push('\\');
+            }
+            _ => entry.push(c),
+        }
+    }
+    entry
+}
+
+/// Returns whether the code can be evaluated, i.e. all strings, definitions and blocks are closed.
+/// Code with other errors, such as mismatched closing words, is complete, so the error can be reported.
+fn is_complete(code: &str) -> bool {
+    let tokens = match tokenize(code, None) {
+        Ok(tokens) => tokens,
+        Err((message, _)) => return message != "Unclosed string",
+    };
+
+    let mut open_blocks = vec![];
+    let mut skip_next = false;
+    for token in tokens {
+        let TokenKind::Word(word) = token.kind else {
+            skip_next = false;
+            continue;
+        };
+        if std::mem::take(&mut skip_next) {
+            continue;
+        }
+        skip_next = READS_NEXT_WORD.contains(&word.as_str());
+
+        if let Some((_, close)) = BLOCKS.iter().find(|(open, _)| *open == word) {
+            open_blocks.push(*close);
+        } else if open_blocks.last() == Some(&word.as_str()) {
+            open_blocks.pop();
+        } else if BLOCKS.iter().any(|(_, close)| *close == word) {
+            // A closing word that doesn't match the innermost block can't be balanced
+            // by more input, so evaluate it and report the error.
+            return true;
+        }
+    }
+    open_blocks.is_empty()
+}
+
+impl<State: Send> Interpreter<State> {
+    /// Start a REPL on stdin and stdout.
+    /// History is kept in `.stackr_history` in the home directory.
+    pub fn start_repl(&mut self) -> Result<(), Err> {
+        let history_path =
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+        let mut repl = match history_path {
+            Some(path) => Repl::with_history_file(path).unwrap_or_default(),
+            None => Repl::new(),
+        };
+
+        let stdin = std::io::stdin();
+        repl.run(self, stdin.lock(), std::io::stdout().lock())
+            .map_err(|e| (e.to_string(), self.location()))
+    }
+}
+
+impl<State> Interpreter<State> {
+    /// Reset the program state after an error, so evaluation can continue.
+    /// The stack and all defined words are kept.
+    pub fn recover(&mut self) {
+        self.reset_program();
+        self.program_counter_stack.clear();
+        self.frames.clear();
+        self.current_word = None;
+        self.compiling = false;
+        self.compile_buffer = CompiledWord::default();
+        self.break_loop = false;
+        self.read_mode = ReadMode::Off;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run a REPL session on the input and return the output.
+    /// The input and output are borrowed, as with locked stdin and stdout.
+    fn run_session(interpreter: &mut Interpreter<()>, input: &str) -> String {
+        let mut output = vec![];
+        Repl::new()
+            .run(interpreter, input.as_bytes(), &mut output)
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn prints_stack_after_each_entry() {
+        let mut interpreter = Interpreter::new(());
+        let output = run_session(&mut interpreter, "1 2\n+\n");
+        assert_eq!(
+            output,
+            "Use 'repl-exit' to exit REPL mode.\n> [ 1 2 ]\n> [ 3 ]\n> "
+        );
+    }
+
+    #[test]
+    fn definitions_can_span_lines() {
+        let mut interpreter = Interpreter::new(());
+        let input = ": double\n\"\" \"\" \"\"\n2 * ;\n3 double\n";
+        let output = run_session(&mut interpreter, input);
+        assert!(output.contains("> ... ... [ ]\n> [ 6 ]"), "{}", output);
+    }
+
+    #[test]
+    fn blocks_and_strings_can_span_lines() {
+        let mut interpreter = Interpreter::new(());
+        let input = "1 if\n\"a\nb\"\nend\n";
+        run_session(&mut interpreter, input);
+        assert_eq!(interpreter.pop_string().unwrap(), "a\nb");
+    }
+
+    #[test]
+    fn recovers_after_error() {
+        let mut interpreter = Interpreter::new(());
+        let input = ": inc \"\" \"\" \"\" 1 + ;\n1 2\n\"a\" inc\ninc\n";
+        let output = run_session(&mut interpreter, input);
+        assert!(
+            output.contains("stdin:1:18: Expected a number\n> [ 1 3 ]"),
+            "{}",
+            output
+        );
+        assert_eq!(interpreter.stack, [1.0.into(), 3.0.into()]);
+    }
+
+    #[test]
+    fn recovers_after_error_in_nested_word() {
+        let mut interpreter = Interpreter::new(());
+        let input = ": a \"\" \"\" \"\" 1 if drop end ;\n: b \"\" \"\" \"\" begin a loop ;\nb\n7\n";
+        let output = run_session(&mut interpreter, input);
+        assert!(
+            output.ends_with("Stack is empty\n> [ 7 ]\n> "),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn printed_output_is_written_to_session() {
+        let mut interpreter = Interpreter::new(());
+        let output = run_session(&mut interpreter, "\"hi\" print cr 1\n");
+        assert!(output.ends_with("> hi\n[ 1 ]\n> "), "{}", output);
+    }
+
+    #[test]
+    fn exit_stops_repl() {
+        let mut interpreter = Interpreter::new(());
+        let output = run_session(&mut interpreter, "1\nexit\n2\n");
+        assert!(output.ends_with("> [ 1 ]\n> "), "{}", output);
+        assert_eq!(interpreter.exit_code(), Some(0));
+    }
+
+    #[test]
+    fn repl_exit_stops_repl() {
+        let mut interpreter = Interpreter::new(());
+        let output = run_session(&mut interpreter, "repl-exit\n2\n");
+        assert!(!output.contains("[ 2 ]"), "{}", output);
+    }
+
+    #[test]
+    fn debugger_stops_at_breakpoints() {
+        let mut interpreter = Interpreter::new(());
+        let input = ": double \"\" \"\" \"\" 2 * ;\n:break *\n3 double\n:stack\n:rstack\n:n\n";
+        let output = run_session(&mut interpreter, input);
+        assert!(
+            output.contains(
+                "> Breakpoint set: *\n> Breakpoint * at stdin:1:21 in double: *\n(debug) [ 3 2 ]\n(debug) top level stdin:1:3\n(debug) [ 6 ]\n> "
+            ),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn debugger_steps_and_finishes() {
+        let mut interpreter = Interpreter::new(());
+        let input = ": double \"\" \"\" \"\" 2 * ;\n:step\n3 double 1 +\n:s\n:s\n:where\n:f\n:c\n";
+        let output = run_session(&mut interpreter, input);
+        assert!(
+            output.contains(concat!(
+                "> Stopping at the next instruction\n",
+                "> Step at stdin:1:1 in top level: N3\n",
+                "(debug) Step at stdin:1:3 in top level: double\n",
+                "(debug) Step at stdin:1:19 in double: N2\n",
+                "(debug) Step at stdin:1:19 in double: N2\n",
+                "(debug) Step at stdin:1:10 in top level: N1\n",
+                "(debug) [ 7 ]\n> "
+            )),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn breakpoint_commands() {
+        let mut interpreter = Interpreter::new(());
+        let input = ":breakpoints\n:break dup\n:break main.stackr:3\n:breakpoints\n:delete dup\n:delete dup\n:delete\n:breakpoints\n:bogus\n";
+        let output = run_session(&mut interpreter, input);
+        assert_eq!(
+            output,
+            concat!(
+                "Use 'repl-exit' to exit REPL mode.\n",
+                "> No breakpoints\n",
+                "> Breakpoint set: dup\n",
+                "> Breakpoint set: main.stackr:3\n",
+                "> dup\nmain.stackr:3\n",
+                "> Breakpoint deleted: dup\n",
+                "> No breakpoint: dup\n",
+                "> Breakpoints deleted\n",
+                "> No breakpoints\n",
+                "> Unknown command: :bogus. Use :help to list commands.\n",
+                "> "
+            )
+        );
+    }
+
+    #[test]
+    fn end_of_input_continues_from_debugger() {
+        let mut interpreter = Interpreter::new(());
+        run_session(&mut interpreter, ":break +\n1 2 +\n");
+        assert_eq!(interpreter.pop_number().unwrap(), 3.0);
+    }
+
+    #[test]
+    fn is_complete_balances_blocks() {
+        assert!(is_complete("1 2 +"));
+        assert!(!is_complete(": a"));
+        assert!(!is_complete("1 if 2"));
+        assert!(!is_complete("begin 1 if break end"));
+        assert!(!is_complete("\"abc"));
+        assert!(is_complete(
+            ": unless \"\" \"\" \"\" postpone ! postpone if ; immediate"
+        ));
+        assert!(is_complete(": a \"\" \"\" \"\" 1 unless 2 end ;"));
+        assert!(is_complete("struct pos { x y }"));
+        assert!(is_complete(": a 1 if 2 ;"));
+        assert!(is_complete("begin 1 ] 2"));
+    }
+
+    #[test]
+    fn mismatched_closer_reports_error() {
+        let mut interpreter = Interpreter::new(());
+        let output = run_session(&mut interpreter, ": a 1 if 2 ;\n1 2\n");
+        assert!(output.ends_with("> [ 1 2 ]\n> "), "{}", output);
+        assert!(!output.contains("... "), "{}", output);
+    }
+
+    #[test]
+    fn reset_discards_unfinished_entry() {
+        let mut interpreter = Interpreter::new(());
+        let output = run_session(&mut interpreter, ": a\n1 if\n:reset\n1 2\n");
+        assert!(
+            output.ends_with("> ... ... Input discarded\n> [ 1 2 ]\n> "),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn history_is_persisted() {
+        let path = std::env::temp_dir().join(format!(
+            "stackr_history_is_persisted_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut interpreter = Interpreter::new(());
+        let mut repl = Repl::with_history_file(path.clone()).unwrap();
+        let input = "1 2\n: a\n\"\\\\\" \"\" \"\" 1 ;\n";
+        repl.run(&mut interpreter, input.as_bytes(), vec![])
+            .unwrap();
+        assert_eq!(repl.history(), ["1 2", ": a\n\"\\\\\" \"\" \"\" 1 ;"]);
+
+        let repl = Repl::with_history_file(path.clone()).unwrap();
+        assert_eq!(repl.history(), ["1 2", ": a\n\"\\\\\" \"\" \"\" 1 ;"]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
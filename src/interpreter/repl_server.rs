@@ -0,0 +1,387 @@
+use super::*;
+use std::io::{ErrorKind, Read};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+/// A stream a REPL connection reads from and writes to.
+trait Stream: Read + Write {
+    /// Switch the stream between blocking and non-blocking mode.
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()>;
+}
+
+impl Stream for TcpStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+}
+
+#[cfg(unix)]
+impl Stream for UnixStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        UnixStream::set_nonblocking(self, nonblocking)
+    }
+}
+
+/// A socket the server accepts connections on.
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    /// Accept a pending connection, if there is one.
+    fn accept(&self) -> std::io::Result<Option<Box<dyn Stream>>> {
+        let stream: std::io::Result<Box<dyn Stream>> = match self {
+            Listener::Tcp(listener) => listener
+                .accept()
+                .map(|(stream, _)| Box::new(stream) as Box<dyn Stream>),
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener
+                .accept()
+                .map(|(stream, _)| Box::new(stream) as Box<dyn Stream>),
+        };
+        match stream {
+            Ok(stream) => Ok(Some(stream)),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Longest line a client may send. Longer lines close the connection.
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// A client connected to the server.
+struct Connection {
+    stream: Box<dyn Stream>,
+    repl: Repl,
+    /// Bytes received that don't form a full line yet.
+    pending: Vec<u8>,
+    /// Output not yet written to the client.
+    outgoing: Vec<u8>,
+    /// Whether to close the connection once the output is written.
+    closing: bool,
+}
+
+impl Connection {
+    /// Write as much of the queued output as the client accepts without blocking.
+    fn flush(&mut self) -> std::io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the available input, evaluate each full line and write the output.
+    /// No input is read while output is still queued, so a client that doesn't read can't fill the memory.
+    /// Returns false once the connection is closed.
+    fn poll<State>(&mut self, interpreter: &mut Interpreter<State>) -> bool {
+        if self.flush().is_err() {
+            return false;
+        }
+        if self.closing || !self.outgoing.is_empty() {
+            return !(self.closing && self.outgoing.is_empty());
+        }
+
+        let mut buffer = [0; 1024];
+        while self.pending.len() <= MAX_LINE_LENGTH {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return false,
+                Ok(n) => self.pending.extend_from_slice(&buffer[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            }
+        }
+
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+
+            // 'repl-exit' and 'exit' close the connection instead of stopping the host.
+            let repl_mode = std::mem::replace(&mut interpreter.repl_mode, true);
+            let exit_code = interpreter.exit_code.take();
+            let result = self.repl.feed_line(interpreter, &line, &mut self.outgoing);
+            let closed = !interpreter.repl_mode || interpreter.exit();
+            interpreter.repl_mode = repl_mode;
+            interpreter.exit_code = exit_code;
+
+            if result.is_err() || closed {
+                self.closing = true;
+                break;
+            }
+            self.outgoing
+                .extend_from_slice(self.repl.prompt().as_bytes());
+        }
+
+        if !self.closing && self.pending.len() > MAX_LINE_LENGTH {
+            self.pending.clear();
+            self.outgoing.extend_from_slice(
+                format!(
+                    "Line longer than {} bytes, closing connection\n",
+                    MAX_LINE_LENGTH
+                )
+                .as_bytes(),
+            );
+            self.closing = true;
+        }
+
+        if self.flush().is_err() {
+            return false;
+        }
+        !(self.closing && self.outgoing.is_empty())
+    }
+}
+
+/// Serves REPL sessions over a local socket, e.g. for a developer console connected to a running game.
+///
+/// The server never blocks, output is queued until the client reads it. Call `poll` regularly, e.g. once per frame,
+/// to accept connections and evaluate their input on the live interpreter.
+/// Every connection has its own input buffer and history, but all share the interpreter.
+pub struct ReplServer {
+    listener: Listener,
+    connections: Vec<Connection>,
+}
+
+impl ReplServer {
+    /// Listen for connections on a TCP address.
+    /// Anyone who can connect can run code, so bind to a local address such as `127.0.0.1`.
+    pub fn bind_tcp(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self::new(Listener::Tcp(listener)))
+    }
+
+    /// Listen for connections on a Unix socket.
+    #[cfg(unix)]
+    pub fn bind_unix(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self::new(Listener::Unix(listener)))
+    }
+
+    fn new(listener: Listener) -> Self {
+        Self {
+            listener,
+            connections: vec![],
+        }
+    }
+
+    /// The TCP address the server is listening on, if it is a TCP server.
+    pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        match &self.listener {
+            Listener::Tcp(listener) => listener.local_addr().ok(),
+            #[cfg(unix)]
+            Listener::Unix(_) => None,
+        }
+    }
+
+    /// The number of connected clients.
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Accept new connections and evaluate all complete lines received from clients.
+    pub fn poll<State>(&mut self, interpreter: &mut Interpreter<State>) -> std::io::Result<()> {
+        while let Some(stream) = self.listener.accept()? {
+            stream.set_nonblocking(true)?;
+            let mut connection = Connection {
+                stream,
+                repl: Repl::new(),
+                pending: vec![],
+                outgoing: vec![],
+                closing: false,
+            };
+            connection.repl.greet(&mut connection.outgoing)?;
+            let prompt = connection.repl.prompt();
+            connection.outgoing.extend_from_slice(prompt.as_bytes());
+            self.connections.push(connection);
+        }
+
+        self.connections
+            .retain_mut(|connection| connection.poll(interpreter));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// Poll the server until the client received output ending with `expected`.
+    fn poll_until<S: Read>(
+        server: &mut ReplServer,
+        interpreter: &mut Interpreter<()>,
+        client: &mut S,
+        expected: &str,
+    ) -> String {
+        let start = Instant::now();
+        let mut received = vec![];
+        let mut buffer = [0; 64 * 1024];
+        while start.elapsed() < Duration::from_secs(5) {
+            server.poll(interpreter).unwrap();
+            if let Ok(n) = client.read(&mut buffer) {
+                received.extend_from_slice(&buffer[..n]);
+            }
+            let text = String::from_utf8_lossy(&received).to_string();
+            if text.ends_with(expected) {
+                return text;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!(
+            "Timed out waiting for {:?}, got {:?}",
+            expected,
+            String::from_utf8_lossy(&received)
+        );
+    }
+
+    #[test]
+    fn tcp_sessions_share_interpreter() {
+        let mut interpreter = Interpreter::new(());
+        let mut server = ReplServer::bind_tcp("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+
+        let mut a = TcpStream::connect(address).unwrap();
+        a.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+        poll_until(&mut server, &mut interpreter, &mut a, "> ");
+
+        let mut b = TcpStream::connect(address).unwrap();
+        b.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+        poll_until(&mut server, &mut interpreter, &mut b, "> ");
+        assert_eq!(server.connection_count(), 2);
+
+        a.write_all(b": double \"\" \"\" \"\"\n").unwrap();
+        poll_until(&mut server, &mut interpreter, &mut a, "... ");
+        a.write_all(b"2 * ;\n").unwrap();
+        poll_until(&mut server, &mut interpreter, &mut a, "[ ]\n> ");
+
+        b.write_all(b"21 double\n").unwrap();
+        poll_until(&mut server, &mut interpreter, &mut b, "[ 42 ]\n> ");
+        assert_eq!(interpreter.pop_number().unwrap(), 42.0);
+    }
+
+    #[test]
+    fn repl_exit_closes_connection() {
+        let mut interpreter = Interpreter::new(());
+        let mut server = ReplServer::bind_tcp("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        poll_until(&mut server, &mut interpreter, &mut client, "> ");
+
+        client.write_all(b"repl-exit\n").unwrap();
+        poll_until_closed(&mut server, &mut interpreter);
+        assert!(!interpreter.exit());
+    }
+
+    /// Poll the server until all connections are closed.
+    fn poll_until_closed(server: &mut ReplServer, interpreter: &mut Interpreter<()>) {
+        let start = Instant::now();
+        while server.connection_count() > 0 && start.elapsed() < Duration::from_secs(5) {
+            server.poll(interpreter).unwrap();
+        }
+        assert_eq!(server.connection_count(), 0);
+    }
+
+    #[test]
+    fn exit_closes_connection_without_stopping_host() {
+        let mut interpreter = Interpreter::new(());
+        let mut server = ReplServer::bind_tcp("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        poll_until(&mut server, &mut interpreter, &mut client, "> ");
+
+        client.write_all(b"1 3 exit-with\n").unwrap();
+        poll_until_closed(&mut server, &mut interpreter);
+        assert!(!interpreter.exit());
+        interpreter.evaluate("2 +", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 3.0);
+    }
+
+    #[test]
+    fn long_lines_close_connection() {
+        let mut interpreter = Interpreter::new(());
+        let mut server = ReplServer::bind_tcp("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        poll_until(&mut server, &mut interpreter, &mut client, "> ");
+
+        client.write_all(&vec![b'1'; MAX_LINE_LENGTH + 1]).unwrap();
+        let expected = format!(
+            "Line longer than {} bytes, closing connection\n",
+            MAX_LINE_LENGTH
+        );
+        poll_until(&mut server, &mut interpreter, &mut client, &expected);
+        poll_until_closed(&mut server, &mut interpreter);
+        assert!(interpreter.stack.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn clients_that_do_not_read_do_not_block() {
+        let path = std::env::temp_dir().join(format!(
+            "stackr_clients_that_do_not_read_{}.sock",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut interpreter = Interpreter::new(());
+        let mut server = ReplServer::bind_unix(&path).unwrap();
+        let mut client = UnixStream::connect(&path).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        poll_until(&mut server, &mut interpreter, &mut client, "> ");
+
+        // The output is much larger than the socket buffer.
+        client.write_all(b"\"x\" 4000000 repeat\n").unwrap();
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(200) {
+            server.poll(&mut interpreter).unwrap();
+        }
+        assert_eq!(server.connection_count(), 1);
+
+        let output = poll_until(&mut server, &mut interpreter, &mut client, "\" ]\n> ");
+        assert_eq!(output.len(), "[ \"\" ]\n> ".len() + 4_000_000);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_session() {
+        let path = std::env::temp_dir().join(format!(
+            "stackr_unix_socket_session_{}.sock",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut interpreter = Interpreter::new(());
+        let mut server = ReplServer::bind_unix(&path).unwrap();
+        let mut client = UnixStream::connect(&path).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        poll_until(&mut server, &mut interpreter, &mut client, "> ");
+
+        client.write_all(b"1 2 +\n").unwrap();
+        poll_until(&mut server, &mut interpreter, &mut client, "[ 3 ]\n> ");
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+use super::*;
+
+/// Words that define the word following them.
+const DEFINING_WORDS: [&str; 5] = [":", "var", "constant", "value", "defer"];
+
+impl<State> Interpreter<State> {
+    /// Check a program for unmatched definitions and words that are referenced but never defined, without running it.
+    /// Returns every error with its location.
+    pub fn check(&mut self, code: &str, path: Option<PathBuf>) -> Result<(), Vec<Err>> {
+        let start = self.program.len();
+        let result = self.load_program(code, path);
+        let errors = match result {
+            Ok(()) => {
+                let mut errors = self.unmatched_definitions(start);
+                errors.extend(self.undefined_words(start));
+                errors
+            }
+            Err(err) => vec![err],
+        };
+        self.program.truncate(start);
+        self.program_debug_locations.truncate(start);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Find all words in the program from `start` that are neither defined nor defined by the program itself.
+    pub(crate) fn undefined_words(&self, start: usize) -> Vec<Err> {
+        let defining_words: HashSet<Address> = DEFINING_WORDS
+            .iter()
+            .filter_map(|name| self.name_table.get(*name).copied())
+            .collect();
+        let read_mode_start = self.name_table.get("[").copied();
+        let read_mode_end = self.address_cache.read_mode_end;
+        let struct_word = self.name_table.get("struct").copied();
+        let test_word = self.name_table.get("test:").copied();
+
+        // Collect the words defined by the program and the words it uses.
+        let mut defined = HashSet::new();
+        let mut used = vec![];
+        let mut idx = start;
+        while idx < self.program.len() {
+            let Instruction::Address(address) = self.program[idx] else {
+                idx += 1;
+                continue;
+            };
+
+            if Some(address) == read_mode_start {
+                // Words in read mode are pushed as addresses, not run.
+                while idx < self.program.len()
+                    && self.program[idx] != Instruction::Address(read_mode_end)
+                {
+                    idx += 1;
+                }
+            } else if Some(address) == struct_word {
+                idx = self.struct_words(idx, &mut defined);
+                continue;
+            } else if Some(address) == test_word {
+                // Test names are not words, so skip the name.
+                used.push((address, idx));
+                idx += 2;
+                continue;
+            } else if defining_words.contains(&address) {
+                used.push((address, idx));
+                if let Some(Instruction::Address(name)) = self.program.get(idx + 1) {
+                    defined.insert(*name);
+                    idx += 1;
+                }
+            } else {
+                used.push((address, idx));
+            }
+            idx += 1;
+        }
+
+        used.into_iter()
+            .filter(|(address, _)| !self.ram.contains_key(address) && !defined.contains(address))
+            .map(|(address, idx)| {
+                (
+                    format!("Undefined word: {}", self.get_name(address)),
+                    self.program_debug_locations[idx].clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Find all `:` and `test:` without a `;` and all `;` without a definition in the program from `start`.
+    pub(crate) fn unmatched_definitions(&self, start: usize) -> Vec<Err> {
+        let definitions: HashSet<Address> = [":", "test:"]
+            .iter()
+            .filter_map(|name| self.name_table.get(*name).copied())
+            .collect();
+        let read_mode_start = self.name_table.get("[").copied();
+        let read_mode_end = self.address_cache.read_mode_end;
+        let compile_end = self.address_cache.compile_end;
+
+        let mut errors = vec![];
+        // Location of the definition waiting for its `;`.
+        let mut open = None;
+        let mut idx = start;
+        while idx < self.program.len() {
+            let Instruction::Address(address) = self.program[idx] else {
+                idx += 1;
+                continue;
+            };
+            let location = &self.program_debug_locations[idx];
+
+            if open.is_none() && Some(address) == read_mode_start {
+                while idx < self.program.len()
+                    && self.program[idx] != Instruction::Address(read_mode_end)
+                {
+                    idx += 1;
+                }
+            } else if address == compile_end {
+                if open.take().is_none() {
+                    errors.push(("Found ; without a matching :".to_string(), location.clone()));
+                }
+            } else if open.is_none() && definitions.contains(&address) {
+                open = Some(location.clone());
+            }
+            idx += 1;
+        }
+
+        if let Some(location) = open {
+            errors.push(("No ; found, unable to compile".to_string(), location));
+        }
+        errors
+    }
+
+    /// Collect the words defined by the `struct` declaration at `idx`.
+    /// Returns the index after the declaration.
+    fn struct_words(&self, idx: usize, defined: &mut HashSet<Address>) -> usize {
+        let Some(Instruction::Address(name)) = self.program.get(idx + 1) else {
+            return idx + 1;
+        };
+        let name = self.get_name(*name);
+        let mut words = vec![format!("make-{}", name), format!("{}?", name)];
+
+        // Skip the name and the '{'
+        let mut idx = idx + 3;
+        while let Some(Instruction::Address(field)) = self.program.get(idx) {
+            idx += 1;
+            let field = self.get_name(*field);
+            if field == "}" {
+                break;
+            }
+            words.push(format!("{}.{}", name, field));
+            words.push(format!("{}.{}!", name, field));
+        }
+
+        defined.extend(words.iter().filter_map(|word| self.name_table.get(word)));
+        idx
+    }
+
+    /// Combine the errors of `undefined_words` into one error, located at the first undefined word.
+    pub(crate) fn undefined_words_error(errors: Vec<Err>) -> Option<Err> {
+        let (message, location) = errors.first()?.clone();
+        if errors.len() == 1 {
+            return Some((message, location));
+        }
+
+        let words = errors
+            .iter()
+            .map(|(message, location)| {
+                let word = message.trim_start_matches("Undefined word: ");
+                format!("{} at {}", word, location)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some((format!("Undefined words: {}", words), location))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undefined_word_returns_err_before_running() {
+        let mut interpreter = Interpreter::new(());
+        let result = interpreter.evaluate("1 2 +\n3 dupe", None);
+        let (message, location) = result.unwrap_err();
+        assert_eq!(message, "Undefined word: dupe");
+        assert_eq!(location.to_string(), "stdin:2:3");
+        assert!(interpreter.stack.is_empty());
+    }
+
+    #[test]
+    fn all_undefined_words_are_reported() {
+        let mut interpreter = Interpreter::new(());
+        let code = ": a \"\" \"\" \"\" foo ;\n1 if bar end";
+        let errors = interpreter.check(code, None).unwrap_err();
+        let messages: Vec<_> = errors
+            .iter()
+            .map(|(message, location)| format!("{} {}", message, location))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "Undefined word: foo stdin:1:14",
+                "Undefined word: bar stdin:2:6"
+            ]
+        );
+
+        let result = interpreter.evaluate(code, None);
+        assert_eq!(
+            result.unwrap_err().0,
+            "Undefined words: foo at stdin:1:14, bar at stdin:2:6"
+        );
+    }
+
+    #[test]
+    fn forward_references_are_allowed() {
+        let mut interpreter = Interpreter::new(());
+        let code = ": a \"\" \"\" \"\" b ;\n: b \"\" \"\" \"\" 1 ;\na";
+        interpreter.check(code, None).unwrap();
+        interpreter.evaluate(code, None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn defined_words_and_read_mode_are_not_reported() {
+        let mut interpreter = Interpreter::new(());
+        let code = "var x 1 constant y 2 value z defer w [ not defined ] x y z";
+        interpreter.check(code, None).unwrap();
+    }
+
+    #[test]
+    fn unmatched_definitions_are_reported() {
+        let mut interpreter = Interpreter::new(());
+        let errors = interpreter
+            .check(": a \"\" \"\" \"\" 1 ;\n2 ;\ntest: b 1 assert", None)
+            .unwrap_err();
+        let messages: Vec<_> = errors
+            .iter()
+            .map(|(message, location)| format!("{} {}", message, location))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "Found ; without a matching : stdin:2:3",
+                "No ; found, unable to compile stdin:3:1"
+            ]
+        );
+        interpreter
+            .check("[ ; ] : a \"\" \"\" \"\" 1 ;", None)
+            .unwrap();
+    }
+
+    #[test]
+    fn check_does_not_change_program() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.check("1 2 oops", None).unwrap_err();
+        assert!(interpreter.program.is_empty());
+        interpreter.evaluate("1 2 +", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 3.0);
+    }
+}
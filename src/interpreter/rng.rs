@@ -0,0 +1,97 @@
+/// A small deterministic pseudo random number generator (SplitMix64).
+/// The entire state is a single `u64` so it can be saved and restored by the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Seed used when the host does not provide one.
+    pub const DEFAULT_SEED: u64 = 0x5EED_5EED_5EED_5EED;
+
+    /// Create a new generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Create a new generator seeded from OS entropy.
+    pub fn from_entropy() -> Self {
+        use std::hash::{BuildHasher, Hasher};
+
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        if let Ok(duration) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+            hasher.write_u128(duration.as_nanos());
+        }
+        Self::new(hasher.finish())
+    }
+
+    /// The current state of the generator.
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Get the next random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Get a random float in the range 0..1.
+    pub fn next_f32(&mut self) -> f32 {
+        // Use the top 24 bits, as that is the precision of an f32.
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Get a random integer in the range 0..bound. `bound` must be greater than 0.
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn restoring_state_continues_sequence() {
+        let mut a = Rng::new(7);
+        a.next_u64();
+        let mut b = Rng::new(a.state());
+        assert_eq!(a.next_u64(), b.next_u64());
+    }
+
+    #[test]
+    fn next_f32_is_in_unit_range() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            let n = rng.next_f32();
+            assert!((0.0..1.0).contains(&n));
+        }
+    }
+
+    #[test]
+    fn next_below_is_in_range() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            assert!(rng.next_below(6) < 6);
+        }
+    }
+}
@@ -2,9 +2,30 @@ use super::*;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum StackValue {
+    /// An address, e.g. of a variable.
     Address(Address),
+    /// A number or string.
     Value(Value),
+    /// An executable reference to a word. Can be run with `@`.
+    ExecutionToken(Address),
 }
+/// Type names of values that are not records, which record types can't use.
+pub(crate) const BUILT_IN_TYPE_NAMES: [&str; 4] = ["address", "xt", "number", "string"];
+
+impl StackValue {
+    /// The name of the type of the value, as pushed by `type-of`.
+    /// Records use the name of their record type.
+    pub fn type_name(&self) -> &str {
+        match self {
+            StackValue::Address(_) => "address",
+            StackValue::ExecutionToken(_) => "xt",
+            StackValue::Value(Value::Number(_)) => "number",
+            StackValue::Value(Value::String(_)) => "string",
+            StackValue::Value(Value::Record(record)) => record.record_type().name(),
+        }
+    }
+}
+
 impl From<f32> for StackValue {
     fn from(value: f32) -> Self {
         StackValue::Value(Value::Number(value))
@@ -18,7 +39,7 @@ impl From<Address> for StackValue {
 
 impl From<&Address> for StackValue {
     fn from(value: &Address) -> Self {
-        StackValue::Address(value.clone())
+        StackValue::Address(*value)
     }
 }
 impl From<Value> for StackValue {
@@ -69,6 +69,43 @@ impl<State> Interpreter<State> {
         Ok(interpreter.stringify_program())
     }
 
+    /// Returns a value as it is displayed by `print-stack`.
+    pub fn stringify_value(&self, value: &StackValue) -> String {
+        match value {
+            StackValue::Address(address) => self.get_name(*address),
+            StackValue::ExecutionToken(address) => format!("'{}", self.get_name(*address)),
+            StackValue::Value(Value::Number(n)) => format!("{}", n),
+            StackValue::Value(Value::String(s)) => escape_string(s),
+            StackValue::Value(Value::Record(record)) => self.stringify_record(record),
+        }
+    }
+
+    /// Returns a value as a string for user output.
+    /// Strings are used as is, other values are displayed as they are in `print-stack`.
+    pub(crate) fn display_value(&self, value: &StackValue) -> String {
+        match value {
+            StackValue::Value(Value::String(s)) => s.clone(),
+            value => self.stringify_value(value),
+        }
+    }
+
+    /// Returns the stack as it is displayed by `print-stack`.
+    pub fn stringify_stack(&self) -> String {
+        self.stringify_values(&self.stack)
+    }
+
+    /// Returns values formatted like the stack.
+    pub(crate) fn stringify_values(&self, values: &[StackValue]) -> String {
+        let mut buffer = String::new();
+        buffer.push_str("[ ");
+        for value in values.iter() {
+            buffer.push_str(&self.stringify_value(value));
+            buffer.push(' ');
+        }
+        buffer.push(']');
+        buffer
+    }
+
     /// Returns the program as a formatted string.
     pub fn stringify_program(&self) -> String {
         // Tokenize the program
@@ -79,7 +116,7 @@ impl<State> Interpreter<State> {
 
             match instruction {
                 Instruction::PushNumber(n) => tokens.push(format!("{}", n)),
-                Instruction::PushString(s) => tokens.push(format!("\"{}\"", s)),
+                Instruction::PushString(s) => tokens.push(escape_string(&s)),
                 Instruction::Address(address) => {
                     tokens.push(self.get_name(address));
                 }
@@ -105,11 +142,26 @@ impl<State> Interpreter<State> {
                     }
                     tokens.add_newline();
                 }
+                // Test definition
+                "test:" => {
+                    tokens.chomp();
+                    tokens.add_space();
+                    tokens.chomp();
+                    tokens.indent();
+                    tokens.add_newline();
+                }
                 // Function definition end
                 ";" => {
                     tokens.dedent();
                     tokens.add_newline();
                     tokens.chomp();
+
+                    // Keep the immediate marker on the same line as the end of the definition.
+                    if Some("immediate".to_string()) == tokens.peek() {
+                        tokens.add_space();
+                        tokens.chomp();
+                    }
+
                     tokens.add_newline();
                     tokens.add_newline();
                 }
@@ -165,6 +217,15 @@ impl<State> Interpreter<State> {
                     tokens.add_newline();
                     tokens.chomp();
                 }
+                // Postponed words are not formatted as control flow
+                "postpone" => {
+                    if Some('\n') != tokens.last_char() && Some('\t') != tokens.last_char() {
+                        tokens.add_space();
+                    }
+                    tokens.chomp();
+                    tokens.add_space();
+                    tokens.chomp();
+                }
                 "." => {
                     tokens.add_space();
                     tokens.chomp();
@@ -172,7 +233,7 @@ impl<State> Interpreter<State> {
 
                     if let Some(token) = tokens.peek() {
                         match token.as_str() {
-                            ":" | "begin" | "if" => {
+                            ":" | "test:" | "begin" | "if" => {
                                 tokens.add_newline();
                             }
                             _ => {}
@@ -246,6 +307,41 @@ mod tests {
         assert_equal(expected, &actual);
     }
 
+    #[test]
+    fn stringify_escapes_strings_losslessly() {
+        let code = "\"tab\\t quote\\\" \" r#\"raw \"line\"\nnext\\n\"#";
+        let formatted = Interpreter::<()>::format_code(code, None).unwrap();
+        assert_equal(
+            "\"tab\\t quote\\\" \" \"raw \\\"line\\\"\\nnext\\\\n\"\n",
+            &formatted,
+        );
+
+        let reformatted = Interpreter::<()>::format_code(&formatted, None).unwrap();
+        assert_equal(&formatted, &reformatted);
+
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate(&formatted, None).unwrap();
+        assert_eq!(interpreter.pop_string().unwrap(), "raw \"line\"\nnext\\n");
+        assert_eq!(interpreter.pop_string().unwrap(), "tab\t quote\" ");
+    }
+
+    #[test]
+    fn stringify_keeps_immediate_after_definition() {
+        let code = ": unless \"\" \"\" \"\" postpone ! postpone if ; immediate 1";
+        let actual = Interpreter::<()>::format_code(code, None).unwrap();
+        let expected =
+            ": unless\n\t\"\"\n\t\"\"\n\t\"\"\n\t\n\tpostpone ! postpone if\n; immediate\n\n1\n";
+        assert_equal(expected, &actual);
+    }
+
+    #[test]
+    fn stringify_formats_tests() {
+        let code = "test: adds 1 2 + 3 assert-eq ; 1";
+        let actual = Interpreter::<()>::format_code(code, None).unwrap();
+        let expected = "test: adds\n\t1 2 + 3 assert-eq\n;\n\n1\n";
+        assert_equal(expected, &actual);
+    }
+
     #[test]
     fn stringify_program_returns_program_as_string() {
         let mut interpreter = Interpreter::new(());
@@ -0,0 +1,209 @@
+use super::*;
+
+/// A test defined with `test:`.
+#[derive(Debug, Clone)]
+pub(crate) struct ScriptTest {
+    name: String,
+    /// Address of the compiled test.
+    address: Address,
+    /// Location of the name of the test.
+    location: Location,
+}
+
+/// The result of running a test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    /// Name of the test.
+    pub name: String,
+    /// Location of the test definition.
+    pub location: Location,
+    /// The error that failed the test, None if it passed.
+    pub error: Option<Err>,
+}
+
+impl TestResult {
+    /// Whether the test passed.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl std::fmt::Display for TestResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.error {
+            None => write!(f, "ok {}", self.name),
+            Some((message, location)) => {
+                write!(f, "FAILED {} at {}: {}", self.name, location, message)
+            }
+        }
+    }
+}
+
+impl<State> Interpreter<State> {
+    /// Define a test, replacing an existing test with the same name.
+    pub(crate) fn define_test(&mut self, name: String, word: CompiledWord) {
+        let location = self.location();
+        let address = match self.tests.iter_mut().find(|test| test.name == name) {
+            Some(test) => {
+                test.location = location;
+                test.address
+            }
+            None => {
+                let address = self.next_address;
+                self.next_address = self.next_address.next();
+                self.tests.push(ScriptTest {
+                    name,
+                    address,
+                    location,
+                });
+                address
+            }
+        };
+        if self.coverage.is_some() {
+            self.cover_word(address, &word);
+        }
+        self.ram.insert(address, RamValue::Compiled(word));
+    }
+
+    /// Name of a test, if the address is a test.
+    pub(crate) fn test_name(&self, address: Address) -> Option<&str> {
+        self.tests
+            .iter()
+            .find(|test| test.address == address)
+            .map(|test| test.name.as_str())
+    }
+
+    /// Names of all defined tests, in the order they were defined.
+    pub fn test_names(&self) -> Vec<&str> {
+        self.tests.iter().map(|test| test.name.as_str()).collect()
+    }
+
+    /// Run all tests defined with `test:`.
+    /// Every test starts with an empty stack, and the stack is restored afterwards.
+    /// Tests that call `exit` or `exit-with` fail.
+    pub fn run_tests(&mut self) -> Vec<TestResult> {
+        self.tests
+            .clone()
+            .into_iter()
+            .map(|test| self.run_test(test))
+            .collect()
+    }
+
+    fn run_test(&mut self, test: ScriptTest) -> TestResult {
+        let stack = std::mem::take(&mut self.stack);
+        let exit_code = self.exit_code.take();
+
+        let mut result = match self.ram.get(&test.address) {
+            Some(RamValue::Compiled(word)) => self.call_compiled(test.address, word.clone()),
+            _ => Err(("Test not found".to_string(), test.location.clone())),
+        };
+        // A test that exits stops early, so it never passes.
+        if let (Ok(()), Some(code)) = (&result, self.exit_code) {
+            result = Err((
+                format!("Test exited with status code {}", code),
+                test.location.clone(),
+            ));
+        }
+        if result.is_err() {
+            self.recover();
+        }
+
+        self.stack = stack;
+        self.exit_code = exit_code;
+        TestResult {
+            name: test.name,
+            location: test.location,
+            error: result.err(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = ": double \"\" \"\" \"\" 2 * ;
+test: doubles
+    3 double 6 assert-eq
+;
+test: starts-empty 0 assert-stack 1 ;
+test: fails
+    1 double 3 assert-eq
+;";
+
+    #[test]
+    fn runs_all_tests() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate(CODE, Some("double.stackr".into()))
+            .unwrap();
+        interpreter.push_number(42.0);
+
+        let results = interpreter.run_tests();
+        let summary: Vec<String> = results.iter().map(|result| result.to_string()).collect();
+        assert_eq!(
+            summary,
+            [
+                "ok doubles",
+                "ok starts-empty",
+                "FAILED fails at double.stackr:7:16: Assertion failed: expected 3, got 2",
+            ]
+        );
+        assert_eq!(results[0].location.line, 2);
+        assert!(!results[2].passed());
+        assert_eq!(interpreter.stack, [42.0.into()]);
+    }
+
+    #[test]
+    fn redefining_a_test_replaces_it() {
+        let mut interpreter = Interpreter::new(());
+        interpreter.evaluate("test: a 0 assert ;", None).unwrap();
+        interpreter.evaluate("test: a 1 assert ;", None).unwrap();
+        let results = interpreter.run_tests();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].passed());
+    }
+
+    #[test]
+    fn test_names_do_not_define_words() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate("test: dup 1 assert ;\n1 dup", None)
+            .unwrap();
+        assert_eq!(interpreter.stack, [1.0.into(), 1.0.into()]);
+        let result = interpreter.evaluate("test: x 1 assert ;\nx", None);
+        assert_eq!(result.unwrap_err().0, "Undefined word: x");
+    }
+
+    #[test]
+    fn exiting_tests_fail() {
+        let mut interpreter = Interpreter::new(());
+        let code = "test: bails 3 exit-with 0 assert ;\ntest: quits exit ;\ntest: after 1 assert ;";
+        interpreter.evaluate(code, None).unwrap();
+        let summary: Vec<String> = interpreter
+            .run_tests()
+            .iter()
+            .map(|result| result.to_string())
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "FAILED bails at stdin:1:7: Test exited with status code 3",
+                "FAILED quits at stdin:2:7: Test exited with status code 0",
+                "ok after",
+            ]
+        );
+        assert_eq!(interpreter.exit_code(), None);
+    }
+
+    #[test]
+    fn evaluation_continues_after_failed_test() {
+        let mut interpreter = Interpreter::new(());
+        interpreter
+            .evaluate(": f \"\" \"\" \"\" drop ;\ntest: a f ;", None)
+            .unwrap();
+        assert!(!interpreter.run_tests()[0].passed());
+        interpreter.evaluate("1 2 +", None).unwrap();
+        assert_eq!(interpreter.pop_number().unwrap(), 3.0);
+    }
+}
@@ -0,0 +1,192 @@
+use super::*;
+
+/// An instruction about to be executed, passed to the trace hook.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEvent {
+    /// Location of the instruction.
+    pub location: Location,
+    /// The instruction.
+    pub instruction: String,
+    /// The compiled word running, None for the top level program.
+    pub word: Option<String>,
+    /// Number of compiled words on the return stack.
+    pub depth: usize,
+    /// The stack before the instruction runs.
+    pub stack: Vec<StackValue>,
+}
+
+/// How trace events are written by `trace_to`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// Tab separated `location word instruction stack` lines.
+    Text,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+/// A type alias for the hook called before every instruction runs.
+pub type TraceHook<State> = Box<dyn FnMut(&Interpreter<State>, &TraceEvent) + Send>;
+
+impl<State> Interpreter<State> {
+    /// Set the hook called before every instruction runs, returning the previous one.
+    /// Tracing is off while no hook is set.
+    pub fn set_trace_hook(&mut self, hook: Option<TraceHook<State>>) -> Option<TraceHook<State>> {
+        std::mem::replace(&mut self.trace_hook, hook)
+    }
+
+    /// Write a line for every instruction that runs to the output.
+    /// Traces of the same program can be diffed to find where runs diverge.
+    pub fn trace_to(&mut self, mut output: Box<dyn Write + Send>, format: TraceFormat) {
+        self.set_trace_hook(Some(Box::new(move |interpreter, event| {
+            let _ = writeln!(output, "{}", interpreter.format_trace_event(event, format));
+        })));
+    }
+
+    /// Returns an event as a single line, without a trailing newline.
+    pub fn format_trace_event(&self, event: &TraceEvent, format: TraceFormat) -> String {
+        match format {
+            TraceFormat::Text => format!(
+                "{}\t{}\t{}\t{}",
+                event.location,
+                event.word.as_deref().unwrap_or("-"),
+                event.instruction,
+                self.stringify_values(&event.stack)
+            ),
+            TraceFormat::JsonLines => {
+                let word = match &event.word {
+                    Some(word) => json_string(word),
+                    None => "null".to_string(),
+                };
+                let stack: Vec<String> = event
+                    .stack
+                    .iter()
+                    .map(|value| json_string(&self.stringify_value(value)))
+                    .collect();
+                format!(
+                    "{{\"location\":{},\"line\":{},\"column\":{},\"word\":{},\"depth\":{},\"instruction\":{},\"stack\":[{}]}}",
+                    json_string(&event.location.to_string()),
+                    event.location.line,
+                    event.location.column,
+                    word,
+                    event.depth,
+                    json_string(&event.instruction),
+                    stack.join(",")
+                )
+            }
+        }
+    }
+
+    /// Call the trace hook for the instruction, if one is set.
+    pub(crate) fn trace_instruction(&mut self, instruction: &Instruction) {
+        let Some(mut hook) = self.trace_hook.take() else {
+            return;
+        };
+
+        let event = TraceEvent {
+            location: self.location(),
+            instruction: instruction.display_type(self),
+            word: self.current_word.map(|word| self.get_name(word)),
+            depth: self.frames.len(),
+            stack: self.stack.clone(),
+        };
+        hook(self, &event);
+
+        // Keep a hook set by the hook itself.
+        if self.trace_hook.is_none() {
+            self.trace_hook = Some(hook);
+        }
+    }
+}
+
+/// Encode a string as a JSON string literal.
+fn json_string(string: &str) -> String {
+    let mut encoded = String::with_capacity(string.len() + 2);
+    encoded.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '\t' => encoded.push_str("\\t"),
+            '\r' => encoded.push_str("\\r"),
+            c if c.is_control() => encoded.push_str(&format!("\\u{:04x}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = ": double \"\" \"\" \"\" 2 * ;\n3 double";
+
+    /// Trace the code and return the lines written.
+    fn trace(code: &str, format: TraceFormat) -> Vec<String> {
+        let mut interpreter = Interpreter::new(());
+        let output = SharedOutput::new();
+        interpreter.trace_to(Box::new(output.clone()), format);
+        interpreter.evaluate(code, None).unwrap();
+        output.contents().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn text_trace() {
+        let lines = trace(CODE, TraceFormat::Text);
+        assert_eq!(
+            lines[lines.len() - 4..],
+            [
+                "stdin:2:1\t-\tN3\t[ ]",
+                "stdin:2:3\t-\tdouble\t[ 3 ]",
+                "stdin:1:19\tdouble\tN2\t[ 3 ]",
+                "stdin:1:21\tdouble\t*\t[ 3 2 ]",
+            ]
+        );
+    }
+
+    #[test]
+    fn json_lines_trace() {
+        let lines = trace("\"a\\\"b\" 1", TraceFormat::JsonLines);
+        assert_eq!(
+            lines,
+            [
+                r#"{"location":"stdin:1:1","line":1,"column":1,"word":null,"depth":0,"instruction":"\"a\\\"b\"","stack":[]}"#,
+                r#"{"location":"stdin:1:8","line":1,"column":8,"word":null,"depth":0,"instruction":"N1","stack":["\"a\\\"b\""]}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn traces_are_identical_between_runs() {
+        let code = "1 if 2 else 3 end begin dup 1 + dup 5 > if break end loop";
+        assert_eq!(
+            trace(code, TraceFormat::Text),
+            trace(code, TraceFormat::Text)
+        );
+    }
+
+    #[test]
+    fn hook_receives_events() {
+        let mut interpreter = Interpreter::new(());
+        let depths = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let recorded = depths.clone();
+        interpreter.set_trace_hook(Some(Box::new(move |_, event| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((event.instruction.clone(), event.depth));
+        })));
+        interpreter.evaluate(CODE, None).unwrap();
+        let events = depths.lock().unwrap().clone();
+        assert_eq!(
+            events[events.len() - 2..],
+            [("N2".to_string(), 1), ("*".to_string(), 1)]
+        );
+
+        interpreter.set_trace_hook(None);
+        interpreter.evaluate("1 2 +", None).unwrap();
+        assert_eq!(depths.lock().unwrap().len(), events.len());
+    }
+}